@@ -0,0 +1,214 @@
+//! Derive macros of the dreamadness game.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, parse_macro_input, spanned::Spanned};
+
+/// Implements `AssetCollection` for an asset collection, and registers it so
+/// the app loads it like `LoadResource::load_resource` would, without being told to.
+///
+/// Each `Handle` field is loaded from the path given by its `asset` attribute:
+///
+/// - `#[asset(path = "images/player.png")]` loads a path directly.
+/// - `#[asset(group = "gameplay")]` loads the asset named like the field in that group of the
+///   `AssetManifest`, and `#[asset(group = "gameplay", name = "player")]` picks another name.
+///   If the manifest has no such asset, the collection isn't loaded and the load fails.
+///
+/// Fields without the attribute are left to their `Default`. The collection is loaded with the
+/// manifest group of its fields, which must all be in the same one.
+///
+/// The `asset_collection` attribute of the struct configures the rest:
+///
+/// - `#[asset_collection(fallback)]` loads it like `LoadResource::load_resource_with_fallback`.
+/// - `#[asset_collection(manual)]` doesn't register it, so only the apps that add it with
+///   `LoadResource` load it, e.g. in tests.
+/// - `#[asset_collection(module = "path::to::asset_tracking")]` tells where the
+///   `asset_tracking` module is, `crate::asset_tracking` by default.
+///
+/// ```ignore
+/// #[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
+/// #[asset_collection(fallback)]
+/// pub struct CreditsAssets {
+///     #[dependency]
+///     #[asset(group = "credits")]
+///     pub music: Handle<AudioSample>,
+/// }
+/// ```
+#[proc_macro_derive(AssetCollection, attributes(asset, asset_collection))]
+pub fn derive_asset_collection(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_asset_collection(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Where a field of an asset collection is loaded from.
+enum AssetSource {
+    Path(LitStr),
+    Manifest { group: LitStr, name: LitStr },
+}
+
+fn expand_asset_collection(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "`AssetCollection` can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "`AssetCollection` needs named fields",
+        ));
+    };
+
+    let options = collection_options(&input)?;
+    let module = &options.module;
+    let mut collection_group: Option<LitStr> = None;
+    let mut initializers = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named fields have an ident");
+        let value = match asset_source(field)? {
+            Some(AssetSource::Path(path)) => quote! { assets.load(#path) },
+            Some(AssetSource::Manifest { group, name }) => {
+                if let Some(other) = &collection_group
+                    && other.value() != group.value()
+                {
+                    return Err(syn::Error::new(
+                        group.span(),
+                        format!("the collection is already in the `{}` group", other.value()),
+                    ));
+                }
+                let value = quote! { assets.load(manifest.path(#group, #name)?) };
+                collection_group = Some(group);
+                value
+            }
+            None => quote! { ::core::default::Default::default() },
+        };
+        initializers.push(quote! { #ident: #value });
+    }
+
+    let manifest = collection_group.is_some().then(|| {
+        quote! {
+            let manifest = world.resource::<#module::AssetManifest>();
+        }
+    });
+    let group = match &collection_group {
+        Some(group) => quote! { ::core::option::Option::Some(#group) },
+        None => quote! { ::core::option::Option::None },
+    };
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "`AssetCollection` can't register generic collections",
+        ));
+    }
+    let load = if options.fallback {
+        quote! { load_resource_with_fallback }
+    } else {
+        quote! { load_resource }
+    };
+    let registration = (!options.manual).then(|| {
+        quote! {
+            #module::inventory::submit! {
+                #module::CollectionRegistration(|app| {
+                    #module::LoadResource::#load::<#ident>(app);
+                })
+            }
+        }
+    });
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #registration
+
+        impl #impl_generics #module::AssetCollection for #ident #type_generics
+            #where_clause
+        {
+            const GROUP: ::core::option::Option<&'static str> = #group;
+
+            fn load(
+                world: &mut ::bevy::prelude::World,
+            ) -> ::core::result::Result<Self, #module::LoadFailure> {
+                #manifest
+                let assets = world.resource::<::bevy::prelude::AssetServer>();
+                ::core::result::Result::Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+/// The `asset_collection` attribute of the struct.
+struct CollectionOptions {
+    fallback: bool,
+    manual: bool,
+    module: syn::Path,
+}
+
+fn collection_options(input: &DeriveInput) -> syn::Result<CollectionOptions> {
+    let mut options = CollectionOptions {
+        fallback: false,
+        manual: false,
+        module: syn::parse_quote!(crate::asset_tracking),
+    };
+    for attr in &input.attrs {
+        if !attr.path().is_ident("asset_collection") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("fallback") {
+                options.fallback = true;
+            } else if meta.path.is_ident("manual") {
+                options.manual = true;
+            } else if meta.path.is_ident("module") {
+                options.module = meta.value()?.parse::<LitStr>()?.parse()?;
+            } else {
+                return Err(meta.error("expected `fallback`, `manual` or `module`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+fn asset_source(field: &syn::Field) -> syn::Result<Option<AssetSource>> {
+    let Some(attr) = field
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("asset"))
+    else {
+        return Ok(None);
+    };
+    let mut path = None;
+    let mut group = None;
+    let mut name = None;
+    attr.parse_nested_meta(|meta| {
+        let slot = if meta.path.is_ident("path") {
+            &mut path
+        } else if meta.path.is_ident("group") {
+            &mut group
+        } else if meta.path.is_ident("name") {
+            &mut name
+        } else {
+            return Err(meta.error("expected `path`, `group` or `name`"));
+        };
+        *slot = Some(meta.value()?.parse::<LitStr>()?);
+        Ok(())
+    })?;
+
+    match (path, group, name) {
+        (Some(path), None, None) => Ok(Some(AssetSource::Path(path))),
+        (None, Some(group), name) => {
+            let ident = field.ident.as_ref().expect("named fields have an ident");
+            let name = name.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+            Ok(Some(AssetSource::Manifest { group, name }))
+        }
+        _ => Err(syn::Error::new(
+            attr.span(),
+            "expected either `path = \"...\"` or `group = \"...\"`",
+        )),
+    }
+}
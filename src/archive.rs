@@ -0,0 +1,389 @@
+//! The packed asset archive, a single file holding the whole `assets` folder for distribution.
+//!
+//! The archive is made with the `dreamadness-pack` binary. When a file named [`ARCHIVE_NAME`]
+//! sits next to the executable, the game reads its assets from it instead of the `assets`
+//! folder. Dev builds always read the loose files, so hot reloading keeps working.
+//!
+//! The format is little-endian: the [`MAGIC`] bytes, the number of entries as a `u32`, then for
+//! each entry its path length as a `u16`, its UTF-8 path, its offset, stored size and original
+//! size as `u64`s and its [`Compression`] as a `u8`. The file contents follow the index, and
+//! offsets are relative to the end of the index.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bevy::{
+    asset::io::{AssetReader, AssetReaderError, PathStream, VecReader},
+    tasks::futures_lite::stream,
+};
+
+/// The file name of the archive, next to the executable.
+pub const ARCHIVE_NAME: &str = "assets.pak";
+
+/// The first bytes of an archive, with the format version.
+pub const MAGIC: &[u8; 8] = b"DRMPAK\x00\x01";
+
+/// How the contents of an entry are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Compression {
+    None = 0,
+    Deflate = 1,
+}
+
+/// Where a file is in the archive.
+#[derive(Debug, Clone, Copy)]
+pub struct ArchiveEntry {
+    pub offset: u64,
+    pub stored_size: u64,
+    pub size: u64,
+    pub compression: Compression,
+}
+
+impl ArchiveEntry {
+    /// Whether the entry is within `data_len` bytes of file contents.
+    fn fits_in(&self, data_len: u64) -> bool {
+        self.offset
+            .checked_add(self.stored_size)
+            .is_some_and(|end| end <= data_len)
+    }
+}
+
+/// What was written by [`pack`].
+#[derive(Debug, Default)]
+pub struct PackStats {
+    pub files: usize,
+    pub compressed: usize,
+    pub size: u64,
+    pub stored_size: u64,
+}
+
+/// Packs every file under `root` into an archive at `output`. With `compress`, files are
+/// deflated when that makes them smaller. An `output` under `root` isn't packed into itself.
+pub fn pack(root: &Path, output: &Path, compress: bool) -> io::Result<PackStats> {
+    let mut files = Vec::new();
+    let output_path = fs::canonicalize(output).ok();
+    collect_files(root, root, output_path.as_deref(), &mut files)?;
+    files.sort();
+
+    let mut stats = PackStats::default();
+    let mut index = Vec::new();
+    let mut data = Vec::new();
+    for path in &files {
+        let contents = fs::read(root.join(path))?;
+        let deflated = compress
+            .then(|| miniz_oxide::deflate::compress_to_vec(&contents, 6))
+            .filter(|deflated| deflated.len() < contents.len());
+        let (stored, compression) = match &deflated {
+            Some(deflated) => (deflated.as_slice(), Compression::Deflate),
+            None => (contents.as_slice(), Compression::None),
+        };
+
+        // Archive paths always use forward slashes, like asset paths.
+        let name = path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path too long"))?;
+        index.extend_from_slice(&name_len.to_le_bytes());
+        index.extend_from_slice(name.as_bytes());
+        index.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        index.extend_from_slice(&(stored.len() as u64).to_le_bytes());
+        index.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        index.push(compression as u8);
+        data.extend_from_slice(stored);
+
+        stats.files += 1;
+        stats.compressed += usize::from(compression == Compression::Deflate);
+        stats.size += contents.len() as u64;
+        stats.stored_size += stored.len() as u64;
+    }
+
+    let count = u32::try_from(files.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many files"))?;
+    let mut file = io::BufWriter::new(File::create(output)?);
+    file.write_all(MAGIC)?;
+    file.write_all(&count.to_le_bytes())?;
+    file.write_all(&index)?;
+    file.write_all(&data)?;
+    file.flush()?;
+    Ok(stats)
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    skip: Option<&Path>,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, skip, files)?;
+        } else if let Some(skip) = skip
+            && fs::canonicalize(&path)? == skip
+        {
+            continue;
+        } else {
+            let relative = path.strip_prefix(root).map_err(io::Error::other)?;
+            files.push(relative.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// Reads assets from an archive. Only the index is kept in memory.
+#[derive(Debug, Clone)]
+pub struct ArchiveReader {
+    path: PathBuf,
+    entries: Arc<BTreeMap<PathBuf, ArchiveEntry>>,
+    data_start: u64,
+}
+
+impl ArchiveReader {
+    /// Reads the index of the archive at `path`.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let mut file = BufReader::new(File::open(&path)?);
+        let mut magic = [0; 8];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not an asset archive, or of another version"));
+        }
+        let count = u32::from_le_bytes(read_array(&mut file)?);
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let name_len = u16::from_le_bytes(read_array(&mut file)?);
+            let mut name = vec![0; name_len as usize];
+            file.read_exact(&mut name)?;
+            let name = String::from_utf8(name).map_err(invalid_data)?;
+            let offset = u64::from_le_bytes(read_array(&mut file)?);
+            let stored_size = u64::from_le_bytes(read_array(&mut file)?);
+            let size = u64::from_le_bytes(read_array(&mut file)?);
+            let compression = match read_array::<1>(&mut file)?[0] {
+                0 => Compression::None,
+                1 => Compression::Deflate,
+                other => return Err(invalid_data(format!("unknown compression {other}"))),
+            };
+            entries.insert(
+                PathBuf::from(name),
+                ArchiveEntry {
+                    offset,
+                    stored_size,
+                    size,
+                    compression,
+                },
+            );
+        }
+        let data_start = file.stream_position()?;
+        let data_len = file.get_ref().metadata()?.len().saturating_sub(data_start);
+        if let Some((name, _)) = entries.iter().find(|(_, entry)| !entry.fits_in(data_len)) {
+            return Err(invalid_data(format!(
+                "entry {} is past the end of the archive",
+                name.display()
+            )));
+        }
+        Ok(Self {
+            path,
+            entries: Arc::new(entries),
+            data_start,
+        })
+    }
+
+    /// The files of the archive, by path.
+    pub fn entries(&self) -> &BTreeMap<PathBuf, ArchiveEntry> {
+        &self.entries
+    }
+
+    /// Reads and decompresses the file at `path`.
+    pub fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let entry = self
+            .entries
+            .get(path)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+        let mut file = File::open(&self.path)?;
+        // The archive may have been truncated since it was opened, don't allocate for data that
+        // isn't there.
+        let data_len = file.metadata()?.len().saturating_sub(self.data_start);
+        if !entry.fits_in(data_len) {
+            return Err(invalid_data(format!("truncated entry {}", path.display())));
+        }
+        file.seek(SeekFrom::Start(self.data_start + entry.offset))?;
+        let mut stored = vec![0; entry.stored_size as usize];
+        file.read_exact(&mut stored)?;
+        let contents = match entry.compression {
+            Compression::None => stored,
+            // Don't inflate past the size the index gives either.
+            Compression::Deflate => {
+                miniz_oxide::inflate::decompress_to_vec_with_limit(&stored, entry.size as usize)
+                    .map_err(|e| invalid_data(format!("corrupt entry {}: {e}", path.display())))?
+            }
+        };
+        if contents.len() as u64 != entry.size {
+            return Err(invalid_data(format!("corrupt entry {}", path.display())));
+        }
+        Ok(contents)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.entries
+            .keys()
+            .any(|entry| entry != path && entry.starts_with(path))
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl AssetReader for ArchiveReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<VecReader, AssetReaderError> {
+        match self.read_file(path) {
+            Ok(bytes) => Ok(VecReader::new(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(AssetReaderError::NotFound(path.to_path_buf()))
+            }
+            Err(e) => Err(AssetReaderError::Io(Arc::new(e))),
+        }
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<VecReader, AssetReaderError> {
+        // Meta files are packed like any other file.
+        let mut meta = path.as_os_str().to_owned();
+        meta.push(".meta");
+        self.read(Path::new(&meta)).await
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        if !self.is_dir(path) {
+            return Err(AssetReaderError::NotFound(path.to_path_buf()));
+        }
+        // Direct children only, files and directories alike.
+        let mut children: Vec<PathBuf> = self
+            .entries
+            .keys()
+            .filter_map(|entry| entry.strip_prefix(path).ok()?.components().next())
+            .map(|child| path.join(child))
+            .collect();
+        children.dedup();
+        Ok(Box::new(stream::iter(children)))
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(self.is_dir(path))
+    }
+}
+
+/// Makes the archive next to the executable the default asset source, if there is one. Must be
+/// called before the `AssetPlugin` is added.
+#[cfg(not(any(feature = "dev", target_family = "wasm")))]
+pub(crate) fn register_asset_source(app: &mut bevy::prelude::App) {
+    use bevy::{
+        asset::io::{AssetSourceBuilder, AssetSourceId},
+        prelude::*,
+    };
+
+    let Some(path) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join(ARCHIVE_NAME)))
+        .filter(|path| path.is_file())
+    else {
+        return;
+    };
+    // Logging isn't set up yet, so the outcome is logged on startup.
+    match ArchiveReader::open(&path) {
+        Ok(reader) => {
+            app.register_asset_source(
+                AssetSourceId::Default,
+                AssetSourceBuilder::new(move || Box::new(reader.clone())),
+            );
+            app.add_systems(Startup, move || {
+                info!("Reading assets from {}", path.display());
+            });
+        }
+        Err(e) => {
+            app.add_systems(Startup, move || {
+                error!(
+                    "Ignoring the asset archive {}, reading loose assets: {e}",
+                    path.display()
+                );
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::io::Reader, tasks::block_on};
+
+    use super::*;
+
+    /// Reads `path` through the asset source, like the asset server does.
+    fn read_asset(reader: &ArchiveReader, path: &str) -> Result<Vec<u8>, AssetReaderError> {
+        block_on(async {
+            let mut asset = reader.read(Path::new(path)).await?;
+            let mut bytes = Vec::new();
+            asset.read_to_end(&mut bytes).await?;
+            Ok(bytes)
+        })
+    }
+
+    #[test]
+    fn round_trips_through_the_asset_source() {
+        let dir = std::env::temp_dir().join(format!("dreamadness-archive-{}", std::process::id()));
+        let root = dir.join("assets");
+        fs::create_dir_all(root.join("levels/fish_level")).unwrap();
+        let repeated = "fish ".repeat(100);
+        fs::write(root.join("manifest.ron"), "()").unwrap();
+        fs::write(root.join("levels/fish_level/food.txt"), &repeated).unwrap();
+
+        let output = dir.join(ARCHIVE_NAME);
+        let stats = pack(&root, &output, true).unwrap();
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.compressed, 1);
+        let reader = ArchiveReader::open(&output).unwrap();
+
+        assert_eq!(read_asset(&reader, "manifest.ron").unwrap(), b"()");
+        assert_eq!(
+            read_asset(&reader, "levels/fish_level/food.txt").unwrap(),
+            repeated.as_bytes()
+        );
+        assert!(matches!(
+            read_asset(&reader, "levels/fish_level/missing.txt"),
+            Err(AssetReaderError::NotFound(path)) if path == Path::new("levels/fish_level/missing.txt")
+        ));
+        assert!(block_on(reader.is_directory(Path::new("levels/fish_level"))).unwrap());
+        assert!(!block_on(reader.is_directory(Path::new("manifest.ron"))).unwrap());
+
+        // An archive inside the packed folder isn't packed, even once it exists.
+        let inner = root.join(ARCHIVE_NAME);
+        pack(&root, &inner, true).unwrap();
+        assert_eq!(pack(&root, &inner, true).unwrap().files, 2);
+
+        // A truncated archive fails to open instead of reading past its end.
+        let bytes = fs::read(&output).unwrap();
+        fs::write(&output, &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(
+            ArchiveReader::open(&output).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,47 @@
+//! Core assets embedded in the binary with the `embedded_assets` feature, so the game can start
+//! and show its menus without the `assets` folder.
+//!
+//! Embedded assets are loaded from the `embedded://` source, under the same path as in the
+//! `assets` folder. [`AssetManifest`](super::AssetManifest) paths point to them automatically.
+//! The game has no font files, the default font is always embedded by Bevy.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{asset::io::embedded::EmbeddedAssetRegistry, prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    embed_assets(app.world().resource::<EmbeddedAssetRegistry>());
+}
+
+/// Defines the list of embedded assets, and the function that embeds them.
+macro_rules! embedded_assets {
+    ($($path:literal),* $(,)?) => {
+        /// The paths of the embedded assets, relative to the `assets` folder.
+        const EMBEDDED: &[&str] = &[$($path),*];
+
+        fn embed_assets(registry: &EmbeddedAssetRegistry) {
+            $(registry.insert_asset(
+                // The original file, watched for hot reloading.
+                PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/", $path)),
+                Path::new($path),
+                include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/", $path)).as_slice(),
+            );)*
+        }
+    };
+}
+
+embedded_assets!(
+    "manifest.ron",
+    "images/splash.png",
+    "audio/sound_effects/467951__benzix2__ui-button-click.ogg",
+    "audio/sound_effects/623175__aphom000__button-click-selection.wav",
+);
+
+/// The path to load `path` from, its embedded copy if there is one.
+pub(super) fn resolve(path: &str) -> String {
+    if EMBEDDED.contains(&path) {
+        format!("embedded://{path}")
+    } else {
+        path.to_string()
+    }
+}
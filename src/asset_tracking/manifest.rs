@@ -0,0 +1,293 @@
+//! The asset manifest, `assets/manifest.ron`, which lists the game's assets in named groups.
+//!
+//! Collections look their paths up in the [`AssetManifest`] instead of hard-coding them, so they
+//! are only loaded once the manifest has. A group registered for some states with
+//! [`AppAssetGroupExt::add_asset_group`] is only kept loaded while they are active, so levels
+//! don't pile up in memory. Other groups are loaded with the manifest.
+
+use std::collections::BTreeMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, UntypedAssetId, io::Reader},
+    prelude::*,
+};
+use bevy_ecs_ldtk::assets::LdtkProject;
+use bevy_seedling::sample::AudioSample;
+use serde::Deserialize;
+
+use super::{LoadCollection, LoadFailure, ResourceHandles, tracked_resource};
+use crate::audio::SoundSpec;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AssetManifest>()
+        .register_asset_loader(AssetManifestLoader)
+        .init_resource::<AssetGroups>();
+
+    let path = resolve_path(MANIFEST_PATH);
+    let handle = app.world().resource::<AssetServer>().load(path);
+    let dependencies = vec![handle.id().untyped()];
+    app.world_mut()
+        .resource_mut::<ResourceHandles>()
+        .waiting
+        .push_back(tracked_resource::<AssetManifest>(
+            handle,
+            dependencies,
+            None,
+            None,
+        ));
+
+    app.add_systems(Startup, request_unscoped_groups);
+    app.add_systems(
+        PreUpdate,
+        load_requested_groups
+            .before(super::load_resource_assets)
+            .run_if(resource_exists::<AssetManifest>),
+    );
+}
+
+const MANIFEST_PATH: &str = "manifest.ron";
+
+#[derive(Resource, Asset, TypePath, Debug, Clone, Deserialize)]
+pub struct AssetManifest {
+    groups: BTreeMap<String, BTreeMap<String, ManifestEntry>>,
+    /// How sound effects are played, by the name of their asset.
+    #[serde(default)]
+    sounds: BTreeMap<String, SoundSpec>,
+}
+
+/// Loads `manifest.ron` files as an [`AssetManifest`].
+#[derive(TypePath)]
+struct AssetManifestLoader;
+
+impl AssetLoader for AssetManifestLoader {
+    type Asset = AssetManifest;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &(),
+        _: &mut LoadContext<'_>,
+    ) -> Result<AssetManifest, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let manifest: AssetManifest = ron::de::from_bytes(&bytes)?;
+        for (name, spec) in &manifest.sounds {
+            spec.validate()
+                .map_err(|error| format!("invalid `{name}` sound: {error}"))?;
+        }
+        Ok(manifest)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ManifestEntry {
+    path: String,
+    kind: AssetKind,
+}
+
+impl ManifestEntry {
+    /// The path to load the asset from.
+    fn resolved_path(&self) -> String {
+        resolve_path(&self.path)
+    }
+}
+
+/// The path to load `path` from, its embedded copy if there is one.
+fn resolve_path(path: &str) -> String {
+    #[cfg(feature = "embedded_assets")]
+    return super::embedded::resolve(path);
+    #[cfg(not(feature = "embedded_assets"))]
+    path.to_string()
+}
+
+/// The type an asset of the manifest is loaded as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum AssetKind {
+    Audio,
+    Image,
+    Ldtk,
+}
+
+impl AssetKind {
+    fn load(self, assets: &AssetServer, path: String) -> UntypedHandle {
+        match self {
+            Self::Audio => assets.load::<AudioSample>(path).untyped(),
+            Self::Image => assets.load::<Image>(path).untyped(),
+            Self::Ldtk => assets.load::<LdtkProject>(path).untyped(),
+        }
+    }
+}
+
+impl AssetManifest {
+    /// The path of the asset `name` in `group`, or a failure to report with
+    /// [`ResourceHandles::report`] if the manifest has no such asset.
+    pub fn path(&self, group: &str, name: &str) -> Result<String, LoadFailure> {
+        self.groups
+            .get(group)
+            .and_then(|assets| assets.get(name))
+            .map(ManifestEntry::resolved_path)
+            .ok_or_else(|| missing(format!("no `{name}` asset in the `{group}` group")))
+    }
+
+    /// How the sound effect `name` is played, or a failure to report with
+    /// [`ResourceHandles::report`] if the manifest has no such sound.
+    pub fn sound(&self, name: &str) -> Result<SoundSpec, LoadFailure> {
+        self.sounds
+            .get(name)
+            .copied()
+            .ok_or_else(|| missing(format!("no `{name}` sound")))
+    }
+
+    /// Loads the assets of `group` that no collection depends on, and tracks them in the
+    /// [`ResourceHandles`].
+    fn load_group(
+        &self,
+        group: &'static str,
+        claimed: &[UntypedAssetId],
+        assets: &AssetServer,
+        resource_handles: &mut ResourceHandles,
+    ) {
+        let Some(entries) = self.groups.get(group) else {
+            error!("The asset manifest has no `{group}` group");
+            return;
+        };
+        for entry in entries.values() {
+            let handle = entry.kind.load(assets, entry.resolved_path());
+            if !claimed.contains(&handle.id()) {
+                resource_handles.track(handle, Some(group));
+            }
+        }
+    }
+}
+
+/// A failure blaming the manifest for lacking something.
+fn missing(error: String) -> LoadFailure {
+    LoadFailure {
+        path: MANIFEST_PATH.to_string(),
+        error: format!("the asset manifest has {error}"),
+    }
+}
+
+/// The collections registered with [`LoadResource`](super::LoadResource), and the groups
+/// scoped to states.
+#[derive(Resource, Default)]
+struct AssetGroups {
+    collections: Vec<(Option<&'static str>, LoadCollection)>,
+    scoped: Vec<&'static str>,
+}
+
+pub(super) fn register_collection<'a>(
+    app: &'a mut App,
+    group: Option<&'static str>,
+    load: LoadCollection,
+) -> &'a mut App {
+    app.world_mut()
+        .resource_mut::<AssetGroups>()
+        .collections
+        .push((group, load));
+    app
+}
+
+/// Loads the groups that aren't scoped to states along with the manifest.
+fn request_unscoped_groups(
+    asset_groups: Res<AssetGroups>,
+    mut resource_handles: ResMut<ResourceHandles>,
+) {
+    for (group, _) in &asset_groups.collections {
+        if !group.is_some_and(|group| asset_groups.scoped.contains(&group)) {
+            resource_handles.request_group(*group);
+        }
+    }
+}
+
+fn load_requested_groups(world: &mut World) {
+    let mut resource_handles = world.resource_mut::<ResourceHandles>();
+    let requested = std::mem::take(&mut resource_handles.requested);
+    let retried = std::mem::take(&mut resource_handles.retried);
+    for (group, load) in retried {
+        load_collection(world, group, load);
+    }
+    world.resource_scope(|world, asset_groups: Mut<AssetGroups>| {
+        for group in requested {
+            let mut claimed = Vec::new();
+            for (_, load) in asset_groups
+                .collections
+                .iter()
+                .filter(|(collection_group, _)| *collection_group == group)
+            {
+                claimed.extend(load_collection(world, group, *load));
+            }
+            let assets = world.resource::<AssetServer>().clone();
+            let manifest = world.resource::<AssetManifest>().clone();
+            let mut resource_handles = world.resource_mut::<ResourceHandles>();
+            if let Some(group) = group {
+                manifest.load_group(group, &claimed, &assets, &mut resource_handles);
+            }
+            resource_handles.loaded.push(group);
+        }
+    });
+}
+
+/// Starts loading a collection of `group`, and returns the assets it depends on. A collection
+/// that can't start is reported, to try again when retrying.
+fn load_collection(
+    world: &mut World,
+    group: Option<&'static str>,
+    load: LoadCollection,
+) -> Vec<UntypedAssetId> {
+    load(world).unwrap_or_else(|failure| {
+        world
+            .resource_mut::<ResourceHandles>()
+            .report_collection(failure, group, Some(load));
+        Vec::new()
+    })
+}
+
+pub trait AppAssetGroupExt {
+    /// Loads the assets of `group` when entering one of `states`, and releases them when
+    /// leaving them for another state.
+    fn add_asset_group<S: States>(
+        &mut self,
+        group: &'static str,
+        states: impl IntoIterator<Item = S>,
+    ) -> &mut Self;
+}
+
+impl AppAssetGroupExt for App {
+    fn add_asset_group<S: States>(
+        &mut self,
+        group: &'static str,
+        states: impl IntoIterator<Item = S>,
+    ) -> &mut Self {
+        self.world_mut()
+            .resource_mut::<AssetGroups>()
+            .scoped
+            .push(group);
+        let states: Vec<S> = states.into_iter().collect();
+        for state in states.clone() {
+            let states = states.clone();
+            self.add_systems(
+                OnEnter(state.clone()),
+                move |mut resource_handles: ResMut<ResourceHandles>| {
+                    resource_handles.request_group(Some(group));
+                },
+            )
+            .add_systems(
+                OnExit(state),
+                // The state is already the one being entered.
+                move |current: Res<State<S>>, mut resource_handles: ResMut<ResourceHandles>| {
+                    if !states.contains(current.get()) {
+                        resource_handles.release_group(group);
+                    }
+                },
+            );
+        }
+        self
+    }
+}
@@ -0,0 +1,605 @@
+//! A high-level way to load collections of asset handles as resources.
+
+#[cfg(feature = "embedded_assets")]
+mod embedded;
+mod manifest;
+
+use std::{collections::VecDeque, mem};
+
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState, UntypedAssetId},
+    prelude::*,
+};
+
+pub use dreamadness_macros::AssetCollection;
+// Used by the `AssetCollection` derive.
+#[doc(hidden)]
+pub use inventory;
+pub use manifest::{AppAssetGroupExt, AssetManifest};
+
+pub(super) fn plugin(app: &mut App) {
+    #[cfg(feature = "embedded_assets")]
+    app.add_plugins(embedded::plugin);
+    app.init_resource::<ResourceHandles>();
+    app.add_plugins(manifest::plugin);
+    for registration in inventory::iter::<CollectionRegistration> {
+        (registration.0)(app);
+    }
+    app.add_systems(PreUpdate, load_resource_assets);
+}
+
+/// Loads a collection with [`LoadResource`], submitted by its [`macro@AssetCollection`] derive.
+#[doc(hidden)]
+pub struct CollectionRegistration(pub fn(&mut App));
+
+inventory::collect!(CollectionRegistration);
+
+/// A [`Resource`] of asset handles, usually derived with [`macro@AssetCollection`].
+pub trait AssetCollection: Resource + Asset + Clone {
+    /// The [`AssetManifest`] group the collection is loaded with. Collections without a group,
+    /// or whose group isn't added with [`AppAssetGroupExt::add_asset_group`], are loaded as
+    /// soon as the manifest is.
+    const GROUP: Option<&'static str>;
+
+    /// Starts loading the assets of the collection, or fails if the manifest lacks one.
+    fn load(world: &mut World) -> Result<Self, LoadFailure>;
+}
+
+pub trait LoadResource {
+    /// This will load the [`Resource`] as an [`Asset`] with its group. When all of its asset
+    /// dependencies have been loaded, it will be inserted as a resource. This ensures that the
+    /// resource only exists when the assets are ready. Releasing the group removes it.
+    ///
+    /// If one of the assets fails to load, the game shows the load error screen.
+    fn load_resource<T: AssetCollection>(&mut self) -> &mut Self;
+
+    /// Like [`Self::load_resource`], but inserts [`FallbackResource::fallback`] instead if one
+    /// of the assets fails to load.
+    fn load_resource_with_fallback<T: AssetCollection + FallbackResource>(&mut self) -> &mut Self;
+}
+
+/// A resource that can do without its assets.
+pub trait FallbackResource: Resource {
+    /// The resource to use when its assets failed to load.
+    fn fallback(world: &mut World) -> Self;
+}
+
+impl LoadResource for App {
+    fn load_resource<T: AssetCollection>(&mut self) -> &mut Self {
+        self.init_asset::<T>();
+        manifest::register_collection(self, T::GROUP, |world| load_collection::<T>(world, None))
+    }
+
+    fn load_resource_with_fallback<T: AssetCollection + FallbackResource>(&mut self) -> &mut Self {
+        self.init_asset::<T>();
+        manifest::register_collection(self, T::GROUP, |world| {
+            load_collection::<T>(
+                world,
+                Some(|world, _| {
+                    let value = T::fallback(world);
+                    world.insert_resource(value);
+                }),
+            )
+        })
+    }
+}
+
+/// Starts loading a collection, and returns the assets it depends on.
+type LoadCollection = fn(&mut World) -> Result<Vec<UntypedAssetId>, LoadFailure>;
+
+/// Starts loading the collection `T`, and returns the assets it depends on.
+fn load_collection<T: AssetCollection>(
+    world: &mut World,
+    fallback: Option<InsertLoadedResource>,
+) -> Result<Vec<UntypedAssetId>, LoadFailure> {
+    let value = T::load(world)?;
+    let mut dependencies = Vec::new();
+    value.visit_dependencies(&mut |id| dependencies.push(id));
+    let handle = world.resource::<AssetServer>().add(value);
+    world
+        .resource_mut::<ResourceHandles>()
+        .waiting
+        .push_back(tracked_resource(
+            handle,
+            dependencies.clone(),
+            T::GROUP,
+            fallback,
+        ));
+    Ok(dependencies)
+}
+
+/// Tracks an asset that is inserted as a resource once loaded, and removed when released.
+fn tracked_resource<T: Resource + Asset + Clone>(
+    handle: Handle<T>,
+    dependencies: Vec<UntypedAssetId>,
+    group: Option<&'static str>,
+    fallback: Option<InsertLoadedResource>,
+) -> TrackedHandle {
+    TrackedHandle {
+        handle: handle.untyped(),
+        dependencies,
+        insert: |world, handle| {
+            let assets = world.resource::<Assets<T>>();
+            if let Some(value) = assets.get(handle.id().typed::<T>()) {
+                world.insert_resource(value.clone());
+            }
+        },
+        fallback,
+        remove: |world| {
+            world.remove_resource::<T>();
+        },
+        group,
+    }
+}
+
+/// A function that inserts a loaded resource.
+type InsertLoadedResource = fn(&mut World, &UntypedHandle);
+
+struct TrackedHandle {
+    handle: UntypedHandle,
+    /// The assets counted towards the [`LoadProgress`].
+    dependencies: Vec<UntypedAssetId>,
+    insert: InsertLoadedResource,
+    fallback: Option<InsertLoadedResource>,
+    /// Removes what `insert` or `fallback` inserted, so the group is the only owner of the
+    /// handles.
+    remove: fn(&mut World),
+    /// The asset group the handle was loaded for, if any.
+    group: Option<&'static str>,
+}
+
+#[derive(Resource, Default)]
+pub struct ResourceHandles {
+    // Use a queue for waiting assets so they can be cycled through and moved to
+    // `finished` one at a time.
+    waiting: VecDeque<TrackedHandle>,
+    finished: Vec<TrackedHandle>,
+    progress: LoadProgress,
+    failed: Vec<(TrackedHandle, LoadFailure)>,
+    /// Failures that aren't an asset's, e.g. a collection missing from the [`AssetManifest`].
+    reported: Vec<ReportedFailure>,
+    /// Collections that failed to start loading, to load again once the manifest has.
+    retried: Vec<(Option<&'static str>, LoadCollection)>,
+    /// Handles of released groups, whose resources are removed on the next check.
+    released: Vec<TrackedHandle>,
+    /// Groups to load once the [`AssetManifest`] has loaded. `None` stands for the
+    /// collections without a group.
+    requested: Vec<Option<&'static str>>,
+    /// Groups that have been loaded and not released since.
+    loaded: Vec<Option<&'static str>>,
+    /// Assets loaded ahead of time, which nothing waits for.
+    preloads: Vec<Preload>,
+}
+
+struct Preload {
+    handle: UntypedHandle,
+    group: &'static str,
+    /// Whether it failed to load, which is only logged once.
+    failed: bool,
+}
+
+struct ReportedFailure {
+    failure: LoadFailure,
+    group: Option<&'static str>,
+    /// The collection that failed to start loading, if it was one.
+    collection: Option<LoadCollection>,
+}
+
+/// An asset that failed to load, without a fallback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadFailure {
+    /// The path of the asset that failed, or of the asset that depends on it.
+    pub path: String,
+    pub error: String,
+}
+
+/// How many of the tracked assets have loaded, dependencies included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub loaded: usize,
+    pub total: usize,
+}
+
+impl LoadProgress {
+    /// The loaded share of the assets, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+}
+
+impl ResourceHandles {
+    /// Returns true if all requested [`Asset`]s have finished loading and are available as [`Resource`]s.
+    pub fn is_all_done(&self) -> bool {
+        self.waiting.is_empty()
+            && self.failed.is_empty()
+            && self.reported.is_empty()
+            && self.requested.is_empty()
+            && self.retried.is_empty()
+    }
+
+    /// The assets that failed to load. The game can't go on without them.
+    pub fn failures(&self) -> impl Iterator<Item = &LoadFailure> {
+        self.failed
+            .iter()
+            .map(|(_, failure)| failure)
+            .chain(self.reported.iter().map(|reported| &reported.failure))
+    }
+
+    /// Records a failure that isn't an asset's, e.g. an asset missing from the
+    /// [`AssetManifest`], so the game shows it like one. Releasing its `group` or retrying
+    /// forgets it.
+    pub fn report(&mut self, failure: LoadFailure, group: Option<&'static str>) {
+        self.report_collection(failure, group, None);
+    }
+
+    fn report_collection(
+        &mut self,
+        failure: LoadFailure,
+        group: Option<&'static str>,
+        collection: Option<LoadCollection>,
+    ) {
+        if self
+            .reported
+            .iter()
+            .any(|reported| reported.failure == failure && reported.group == group)
+        {
+            return;
+        }
+        error!("Failed to load {}: {}", failure.path, failure.error);
+        self.reported.push(ReportedFailure {
+            failure,
+            group,
+            collection,
+        });
+    }
+
+    /// Tries to load the assets that failed again, e.g. once the player has seen the error.
+    pub fn retry_failed(&mut self, assets: &AssetServer) {
+        for (tracked, _) in self.failed.drain(..) {
+            let failed = tracked
+                .dependencies
+                .iter()
+                .copied()
+                .chain([tracked.handle.id()])
+                .filter(|id| matches!(assets.get_load_state(*id), Some(LoadState::Failed(_))));
+            for id in failed {
+                if let Some(path) = assets.get_path(id) {
+                    assets.reload(path.into_owned());
+                }
+            }
+            self.waiting.push_back(tracked);
+        }
+        // Whatever reported the other failures reports them again if they still happen.
+        for reported in self.reported.drain(..) {
+            if let Some(collection) = reported.collection {
+                self.retried.push((reported.group, collection));
+            }
+        }
+    }
+
+    /// The progress as of the last check, which happens every frame in [`PreUpdate`].
+    pub fn progress(&self) -> LoadProgress {
+        self.progress
+    }
+
+    /// Tracks an asset that isn't a resource, so [`Self::is_all_done`] waits for it too. If it
+    /// belongs to a `group`, it's only kept loaded until the group is released.
+    pub fn track(&mut self, handle: UntypedHandle, group: Option<&'static str>) {
+        let id = handle.id();
+        let tracked = self
+            .finished
+            .iter()
+            .chain(&self.waiting)
+            .any(|tracked| tracked.handle.id() == id && tracked.group == group);
+        if !tracked {
+            self.waiting.push_back(TrackedHandle {
+                handle,
+                dependencies: vec![id],
+                insert: |_, _| {},
+                fallback: None,
+                remove: |_| {},
+                group,
+            });
+        }
+    }
+
+    /// Keeps an asset loaded until `group` is released, without [`Self::is_all_done`] waiting
+    /// for it. Failing to load it is only logged, whatever needs it later tracks it again.
+    pub fn preload(&mut self, handle: UntypedHandle, group: &'static str) {
+        let id = handle.id();
+        if !self
+            .preloads
+            .iter()
+            .any(|preload| preload.handle.id() == id && preload.group == group)
+        {
+            self.preloads.push(Preload {
+                handle,
+                group,
+                failed: false,
+            });
+        }
+    }
+
+    /// Loads `group` once the [`AssetManifest`] has loaded, unless it already is.
+    pub(super) fn request_group(&mut self, group: Option<&'static str>) {
+        if !self.requested.contains(&group) && !self.loaded.contains(&group) {
+            self.requested.push(group);
+        }
+    }
+
+    /// Drops the handles tracked for `group`, so its assets are unloaded unless something
+    /// else still uses them. The resources of its collections are removed, and its failures
+    /// are forgotten.
+    pub fn release_group(&mut self, group: &str) {
+        let in_group = |tracked: &TrackedHandle| tracked.group == Some(group);
+        let (released, waiting) = mem::take(&mut self.waiting)
+            .into_iter()
+            .partition::<Vec<_>, _>(in_group);
+        self.waiting = waiting.into();
+        self.released.extend(released);
+        let (released, finished) = mem::take(&mut self.finished)
+            .into_iter()
+            .partition::<Vec<_>, _>(in_group);
+        self.finished = finished;
+        self.released.extend(released);
+        let (released, failed) = mem::take(&mut self.failed)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(tracked, _)| in_group(tracked));
+        self.failed = failed;
+        self.released
+            .extend(released.into_iter().map(|(tracked, _)| tracked));
+        self.reported
+            .retain(|reported| reported.group != Some(group));
+        self.retried.retain(|(retried, _)| *retried != Some(group));
+        self.preloads.retain(|preload| preload.group != group);
+        self.requested.retain(|requested| *requested != Some(group));
+        self.loaded.retain(|loaded| *loaded != Some(group));
+    }
+}
+
+fn load_resource_assets(world: &mut World) {
+    let assets = world.resource::<AssetServer>().clone();
+    world.resource_scope(|world, mut resource_handles: Mut<ResourceHandles>| {
+        for tracked in mem::take(&mut resource_handles.released) {
+            (tracked.remove)(world);
+        }
+
+        // Finished handles are only kept around to keep their assets loaded.
+        let finished = resource_handles
+            .finished
+            .iter()
+            .map(|tracked| tracked.dependencies.len())
+            .sum();
+        let mut progress = LoadProgress {
+            loaded: finished,
+            total: finished,
+        };
+        for _ in 0..resource_handles.waiting.len() {
+            let tracked = resource_handles.waiting.pop_front().unwrap();
+            progress.total += tracked.dependencies.len();
+            if assets.is_loaded_with_dependencies(&tracked.handle) {
+                progress.loaded += tracked.dependencies.len();
+                (tracked.insert)(world, &tracked.handle);
+                resource_handles.finished.push(tracked);
+            } else if let Some(failure) = load_failure(&assets, &tracked) {
+                // A failed asset is as loaded as it will ever be.
+                progress.loaded += tracked.dependencies.len();
+                if let Some(fallback) = tracked.fallback {
+                    warn!(
+                        "Failed to load {}, using a fallback: {}",
+                        failure.path, failure.error
+                    );
+                    fallback(world, &tracked.handle);
+                    resource_handles.finished.push(tracked);
+                } else {
+                    error!("Failed to load {}: {}", failure.path, failure.error);
+                    resource_handles.failed.push((tracked, failure));
+                }
+            } else {
+                progress.loaded += tracked
+                    .dependencies
+                    .iter()
+                    .filter(|id| assets.is_loaded_with_dependencies(**id))
+                    .count();
+                resource_handles.waiting.push_back(tracked);
+            }
+        }
+        resource_handles.progress = progress;
+
+        for preload in resource_handles
+            .preloads
+            .iter_mut()
+            .filter(|preload| !preload.failed)
+        {
+            if let Some(RecursiveDependencyLoadState::Failed(error)) =
+                assets.get_recursive_dependency_load_state(&preload.handle)
+            {
+                let path = assets
+                    .get_path(preload.handle.id())
+                    .map_or_else(|| "an asset".to_string(), |path| path.to_string());
+                warn!("Failed to preload {path}: {error}");
+                preload.failed = true;
+            }
+        }
+    });
+}
+
+/// Finds out whether the tracked asset or one of its dependencies failed to load, and which.
+fn load_failure(assets: &AssetServer, tracked: &TrackedHandle) -> Option<LoadFailure> {
+    let RecursiveDependencyLoadState::Failed(error) =
+        assets.get_recursive_dependency_load_state(&tracked.handle)?
+    else {
+        return None;
+    };
+    let path_of = |id| {
+        assets
+            .get_path(id)
+            .map_or_else(|| "an asset".to_string(), |path| path.to_string())
+    };
+    // Blame the direct dependency that failed if there is one, the error names the nested
+    // asset otherwise.
+    let failure = tracked
+        .dependencies
+        .iter()
+        .find_map(|id| match assets.get_load_state(*id) {
+            Some(LoadState::Failed(error)) => Some(LoadFailure {
+                path: path_of(*id),
+                error: error.to_string(),
+            }),
+            _ => None,
+        })
+        .unwrap_or_else(|| LoadFailure {
+            path: path_of(tracked.handle.id()),
+            error: error.to_string(),
+        });
+    Some(failure)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::state::FreelyMutableState;
+
+    use super::*;
+    use crate::{
+        AppPlugin,
+        game::fish_level::{LevelAssets, LevelProject},
+        screens::Screen,
+        theme::interaction::InteractionAssets,
+    };
+
+    /// Updates the app until it reaches `screen`, or panics after a while.
+    fn update_until<S: FreelyMutableState>(app: &mut App, screen: S) {
+        for _ in 0..1000 {
+            app.update();
+            if *app.world().resource::<State<S>>().get() == screen {
+                return;
+            }
+        }
+        let failures: Vec<_> = app
+            .world()
+            .resource::<ResourceHandles>()
+            .failures()
+            .cloned()
+            .collect();
+        panic!("never reached {screen:?}: {failures:?}");
+    }
+
+    /// Only loaded by the apps that add it, the other tests don't depend on it.
+    #[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
+    #[asset_collection(manual, module = "super")]
+    struct SplashAssets {
+        #[dependency]
+        #[asset(group = "splash", name = "image")]
+        image: Handle<Image>,
+        #[dependency]
+        #[asset(path = "images/player.png")]
+        player: Handle<Image>,
+    }
+
+    fn headless_app(start_screen: Screen, setup: impl FnOnce(&mut App)) -> App {
+        let mut app = App::new();
+        app.add_plugins(
+            AppPlugin::new()
+                .headless(true)
+                .editor(false)
+                .dev_tools(false)
+                .start_screen(start_screen),
+        );
+        setup(&mut app);
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    #[test]
+    fn derived_collections_load_where_registered() {
+        assert_eq!(SplashAssets::GROUP, Some("splash"));
+        let mut app = headless_app(Screen::Title, |app| {
+            app.load_resource::<SplashAssets>();
+        });
+        let mut other_app = headless_app(Screen::Title, |_| {});
+        for _ in 0..1000 {
+            app.update();
+            other_app.update();
+            // The other collections register themselves.
+            if let Some(assets) = app.world().get_resource::<SplashAssets>()
+                && app.world().contains_resource::<InteractionAssets>()
+                && other_app.world().contains_resource::<InteractionAssets>()
+            {
+                assert_ne!(assets.image, assets.player);
+                assert!(!other_app.world().contains_resource::<SplashAssets>());
+                return;
+            }
+        }
+        panic!("the collections never loaded");
+    }
+
+    #[test]
+    fn retry_after_a_failure_reaches_gameplay() {
+        let mut app = App::new();
+        app.add_plugins(
+            AppPlugin::new()
+                .headless(true)
+                .editor(false)
+                .dev_tools(false)
+                .start_screen(Screen::Loading)
+                .level("levels/missing.ldtk"),
+        );
+        app.finish();
+        app.cleanup();
+        update_until(&mut app, Screen::LoadError);
+
+        // Back to the title, then play the first level.
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Title);
+        update_until(&mut app, Screen::Title);
+        // Leaving the error screen releases the gameplay group, and its collections with it.
+        app.update();
+        assert!(!app.world().contains_resource::<LevelAssets>());
+        *app.world_mut().resource_mut::<LevelProject>() = LevelProject::default();
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Loading);
+        update_until(&mut app, Screen::Gameplay);
+        assert_eq!(
+            app.world().resource::<ResourceHandles>().failures().count(),
+            0
+        );
+    }
+
+    #[test]
+    fn missing_manifest_entries_fail_the_load() {
+        let mut app = headless_app(Screen::Title, |_| {});
+        for _ in 0..1000 {
+            if app.world().contains_resource::<AssetManifest>() {
+                break;
+            }
+            app.update();
+        }
+        let manifest = std::fs::read_to_string("assets/manifest.ron").unwrap();
+        let manifest = manifest
+            .lines()
+            .filter(|line| !line.contains("\"player\""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let manifest: AssetManifest = ron::from_str(&manifest).unwrap();
+        app.world_mut().insert_resource(manifest);
+
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Loading);
+        update_until(&mut app, Screen::LoadError);
+        let failures: Vec<_> = app
+            .world()
+            .resource::<ResourceHandles>()
+            .failures()
+            .map(|failure| failure.path.as_str())
+            .collect();
+        assert_eq!(failures, ["manifest.ron"]);
+    }
+}
@@ -0,0 +1,122 @@
+//! The effect chain of the music bus: a low-pass filter followed by a volume duck.
+//!
+//! The chain muffles the music while the game is paused or a menu is open over the gameplay.
+//! States can also ask for a [`MusicFilter`] of their own with [`AppMusicFilterExt`], like the
+//! fish level which sounds as if heard from under water.
+
+use bevy::prelude::*;
+use bevy_seedling::prelude::{Connect, LowPassNode, Volume, VolumeNode};
+
+use crate::{Pause, menus::Menu, screens::Screen};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MusicFilterRequests>();
+    app.add_systems(Update, fade_music_filter);
+}
+
+/// How quickly the chain moves towards its target settings, higher is faster.
+const FILTER_DECAY_RATE: f32 = 6.0;
+
+/// Settings of the music bus' effect chain.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct MusicFilter {
+    /// Cutoff frequency of the low-pass filter, in hertz.
+    pub cutoff_hz: f32,
+    /// Linear volume of the duck.
+    pub volume: f32,
+}
+
+impl MusicFilter {
+    /// The music as it was recorded.
+    pub const OPEN: Self = Self {
+        cutoff_hz: 20_000.0,
+        volume: 1.0,
+    };
+    /// Muffled and quieter, while the game is paused.
+    pub const PAUSED: Self = Self {
+        cutoff_hz: 500.0,
+        volume: 0.5,
+    };
+    /// Heard from under water.
+    pub const UNDERWATER: Self = Self {
+        cutoff_hz: 1_200.0,
+        volume: 0.9,
+    };
+}
+
+pub trait AppMusicFilterExt {
+    /// Applies `filter` to the music while `state` is active.
+    fn add_music_filter(&mut self, state: impl States, filter: MusicFilter) -> &mut Self;
+}
+
+impl AppMusicFilterExt for App {
+    fn add_music_filter(&mut self, state: impl States, filter: MusicFilter) -> &mut Self {
+        self.add_systems(
+            OnEnter(state.clone()),
+            move |mut requests: ResMut<MusicFilterRequests>| requests.0.push(filter),
+        )
+        .add_systems(
+            OnExit(state),
+            move |mut requests: ResMut<MusicFilterRequests>| {
+                if let Some(i) = requests.0.iter().rposition(|f| *f == filter) {
+                    requests.0.remove(i);
+                }
+            },
+        )
+    }
+}
+
+/// The filters of the active states, in the order they were entered. The last one wins.
+#[derive(Resource, Debug, Default)]
+struct MusicFilterRequests(Vec<MusicFilter>);
+
+#[derive(Component)]
+struct MusicLowPass;
+
+#[derive(Component)]
+struct MusicDuck;
+
+/// Spawns the effect chain and returns its input. Its output goes to the main bus.
+pub(super) fn spawn_music_effects(cmd: &mut Commands) -> Entity {
+    cmd.spawn((
+        Name::new("MusicLowPass"),
+        MusicLowPass,
+        LowPassNode {
+            frequency: MusicFilter::OPEN.cutoff_hz,
+        },
+    ))
+    .chain_node((Name::new("MusicDuck"), MusicDuck, VolumeNode::default()))
+    .head()
+}
+
+fn fade_music_filter(
+    time: Res<Time<Real>>,
+    requests: Res<MusicFilterRequests>,
+    pause: Res<State<Pause>>,
+    screen: Res<State<Screen>>,
+    menu: Res<State<Menu>>,
+    mut low_pass: Single<&mut LowPassNode, With<MusicLowPass>>,
+    mut duck: Single<&mut VolumeNode, With<MusicDuck>>,
+) {
+    let menu_over_gameplay = *screen.get() == Screen::Gameplay && *menu.get() != Menu::None;
+    let target = if pause.get().0 || menu_over_gameplay {
+        MusicFilter::PAUSED
+    } else {
+        requests.0.last().copied().unwrap_or(MusicFilter::OPEN)
+    };
+    let delta = time.delta_secs();
+
+    // Pitch is perceived logarithmically, so the cutoff moves in octaves.
+    let mut octaves = low_pass.frequency.log2();
+    octaves.smooth_nudge(&target.cutoff_hz.log2(), FILTER_DECAY_RATE, delta);
+    let cutoff_hz = octaves.exp2();
+    if (cutoff_hz - low_pass.frequency).abs() > 0.1 {
+        low_pass.frequency = cutoff_hz;
+    }
+
+    let mut volume = duck.volume.linear();
+    volume.smooth_nudge(&target.volume, FILTER_DECAY_RATE, delta);
+    if (volume - duck.volume.linear()).abs() > 1e-4 {
+        duck.volume = Volume::Linear(volume);
+    }
+}
@@ -0,0 +1,169 @@
+mod effects;
+mod music;
+mod positional;
+
+use std::{io, num::NonZeroU32, time::Duration};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use bevy_seedling::{
+    pool::SamplerPool,
+    prelude::{Connect, MainBus, PoolLabel, Volume, VolumeNode},
+    sample::{AudioSample, SamplePlayer},
+    sample_effects,
+};
+
+pub use effects::{AppMusicFilterExt, MusicFilter};
+pub use music::{AppMusicExt, MusicCollection, MusicIntensity, Playlist};
+pub use positional::positional_sound_effect;
+
+use serde::Deserialize;
+
+use self::positional::PositionalSoundPool;
+use crate::settings::Settings;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((effects::plugin, music::plugin, positional::plugin));
+    app.add_systems(
+        Update,
+        // The pools only get their volume node once they are populated.
+        apply_volumes
+            .run_if(resource_changed::<Settings>.or(any_match_filter::<Added<VolumeNode>>)),
+    )
+    .add_systems(Startup, setup);
+}
+
+#[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct MusicPool;
+
+fn setup(mut cmd: Commands) {
+    let music_effects = effects::spawn_music_effects(&mut cmd);
+    cmd.spawn((
+        Name::new("MusicPool"),
+        SamplerPool(MusicPool),
+        // Each track gets its own volume so the music director can crossfade between them.
+        sample_effects![VolumeNode::default()],
+    ))
+    .connect(music_effects);
+    cmd.spawn((Name::new("SoundPool"), SamplerPool(SoundPool)));
+}
+
+#[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct SoundPool;
+
+/// A sound effect audio instance.
+pub fn sound_effect(handle: Handle<AudioSample>) -> impl Bundle {
+    (SamplePlayer::new(handle), SoundPool)
+}
+
+/// A sound effect audio instance with a linear volume.
+pub fn sound_effect_with_volume(handle: Handle<AudioSample>, volume: f32) -> impl Bundle {
+    (
+        SamplePlayer {
+            volume: Volume::Linear(volume),
+            ..SamplePlayer::new(handle)
+        },
+        SoundPool,
+    )
+}
+
+/// Loads audio files as silence, for headless apps that have no audio device to decode them
+/// for. Missing files still fail to load.
+#[derive(TypePath)]
+pub struct SilentSampleLoader;
+
+impl AssetLoader for SilentSampleLoader {
+    type Asset = AudioSample;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &(),
+        _: &mut LoadContext<'_>,
+    ) -> Result<AudioSample, io::Error> {
+        reader.read_to_end(&mut Vec::new()).await?;
+        Ok(AudioSample::new(vec![Vec::<f32>::new()], NonZeroU32::MIN))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["wav", "ogg"]
+    }
+}
+
+/// How a sound effect is played, read from the `sounds` of the asset manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct SoundSpec {
+    /// Linear volume.
+    pub volume: f32,
+    /// The volume is picked in `volume ± volume_variation`.
+    #[serde(default)]
+    pub volume_variation: f32,
+    /// The playback speed, and thus the pitch, is picked in `1 ± pitch_variation`.
+    #[serde(default)]
+    pub pitch_variation: f64,
+    /// The sound doesn't play again until this many seconds have passed.
+    #[serde(default)]
+    pub cooldown_secs: f32,
+}
+
+impl SoundSpec {
+    pub fn cooldown(&self) -> Duration {
+        Duration::from_secs_f32(self.cooldown_secs)
+    }
+
+    /// Checks that the variations and the cooldown can be played, which negative ones can't.
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            ("volume_variation", self.volume_variation as f64),
+            ("pitch_variation", self.pitch_variation),
+            ("cooldown_secs", self.cooldown_secs as f64),
+        ];
+        match fields
+            .iter()
+            .find(|(_, value)| !(value.is_finite() && *value >= 0.0))
+        {
+            Some((field, value)) => Err(format!("`{field}` can't be {value}")),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Drives the main bus and each pool's volume node from the [`Settings`].
+fn apply_volumes(
+    settings: Res<Settings>,
+    mut main_bus: Query<&mut VolumeNode, With<MainBus>>,
+    mut music_pool: Query<&mut VolumeNode, (With<SamplerPool<MusicPool>>, Without<MainBus>)>,
+    mut sound_pools: Query<
+        &mut VolumeNode,
+        (
+            Or<(
+                With<SamplerPool<SoundPool>>,
+                With<SamplerPool<PositionalSoundPool>>,
+            )>,
+            Without<MainBus>,
+            Without<SamplerPool<MusicPool>>,
+        ),
+    >,
+) {
+    for mut node in &mut main_bus {
+        node.volume = Volume::Linear(settings.master_volume);
+    }
+    for mut node in &mut music_pool {
+        node.volume = bus_volume(settings.music_volume, settings.music_muted);
+    }
+    for mut node in &mut sound_pools {
+        node.volume = bus_volume(settings.sfx_volume, settings.sfx_muted);
+    }
+}
+
+fn bus_volume(volume: f32, muted: bool) -> Volume {
+    if muted {
+        Volume::SILENT
+    } else {
+        Volume::Linear(volume)
+    }
+}
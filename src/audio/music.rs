@@ -0,0 +1,265 @@
+//! The music director, which owns the music and crossfades between tracks.
+//!
+//! Screens, menus and levels don't spawn music themselves. They declare it through their asset
+//! collection with [`MusicCollection`], and register it for a state with
+//! [`AppMusicExt::add_music`]. The most recently entered state with music wins, and leaving it
+//! fades back to the music of the state below.
+//! A collection can also be inserted or removed while its state is active, and the music follows.
+//!
+//! A track can be layered: its [`Stem`]s play in sync and fade in and out with the
+//! [`MusicIntensity`], which gameplay sets to follow what's happening.
+
+use std::{any::TypeId, ops::Range};
+
+use bevy::prelude::*;
+use bevy_seedling::{
+    prelude::{EffectsQuery, RepeatMode, SampleEffects, Volume, VolumeNode},
+    sample::{AudioSample, SamplePlayer},
+    sample_effects,
+};
+
+use super::MusicPool;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MusicDirector>();
+    app.init_resource::<MusicIntensity>();
+    app.add_systems(Update, (update_music, fade_music).chain());
+}
+
+/// How long it takes for a track to fade into the next one.
+const CROSSFADE_SECS: f32 = 1.5;
+/// How quickly stems follow the [`MusicIntensity`], higher is faster.
+const STEM_DECAY_RATE: f32 = 2.0;
+
+/// One layer of a [`Track`], audible once the [`MusicIntensity`] reaches its `fade_in` range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stem {
+    pub sample: Handle<AudioSample>,
+    /// The stem is silent below the start of the range and fully audible past its end.
+    pub fade_in: Range<f32>,
+}
+
+impl Stem {
+    /// A stem that always plays.
+    pub fn base(sample: Handle<AudioSample>) -> Self {
+        Self {
+            sample,
+            fade_in: 0.0..0.0,
+        }
+    }
+
+    /// The volume of the stem at `intensity`.
+    fn gain(&self, intensity: f32) -> f32 {
+        let Range { start, end } = self.fade_in;
+        if end > start {
+            ((intensity - start) / (end - start)).clamp(0.0, 1.0)
+        } else if intensity >= start {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Stems of the same length, started together so they stay in sync.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track(pub Vec<Stem>);
+
+impl From<Handle<AudioSample>> for Track {
+    fn from(sample: Handle<AudioSample>) -> Self {
+        Self(vec![Stem::base(sample)])
+    }
+}
+
+/// Tracks that play one after the other, looping back to the first one.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Playlist(pub Vec<Track>);
+
+impl Playlist {
+    pub fn single(track: impl Into<Track>) -> Self {
+        Self(vec![track.into()])
+    }
+}
+
+/// How intense the game currently is, from 0 to 1. Layered music brings in more of its stems as
+/// it rises.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct MusicIntensity(pub f32);
+
+/// An asset collection that declares the music of a screen, menu or level.
+pub trait MusicCollection: Resource {
+    fn playlist(&self) -> Playlist;
+}
+
+pub trait AppMusicExt {
+    /// Plays the music of `A` while `state` is active.
+    fn add_music<A: MusicCollection>(&mut self, state: impl States) -> &mut Self;
+}
+
+impl AppMusicExt for App {
+    fn add_music<A: MusicCollection>(&mut self, state: impl States) -> &mut Self {
+        self.add_systems(OnEnter(state.clone()), request_music::<A>)
+            .add_systems(Update, refresh_music::<A>.run_if(in_state(state.clone())))
+            .add_systems(OnExit(state), release_music::<A>)
+    }
+}
+
+#[derive(Resource, Default)]
+struct MusicDirector {
+    /// The playlists of the active states, in the order they were entered.
+    requests: Vec<(TypeId, Playlist)>,
+    current: Option<Playlist>,
+    track: usize,
+}
+
+fn request_music<A: MusicCollection>(collection: If<Res<A>>, mut director: ResMut<MusicDirector>) {
+    director.requests.retain(|(id, _)| *id != TypeId::of::<A>());
+    director
+        .requests
+        .push((TypeId::of::<A>(), collection.playlist()));
+}
+
+/// Follows the collection while its state is active, e.g. a level that picks its music once it
+/// has spawned. The request keeps its place.
+fn refresh_music<A: MusicCollection>(
+    collection: Option<Res<A>>,
+    mut director: ResMut<MusicDirector>,
+) {
+    let Some(collection) = collection else {
+        director.requests.retain(|(id, _)| *id != TypeId::of::<A>());
+        return;
+    };
+    if !collection.is_changed() {
+        return;
+    }
+    let playlist = collection.playlist();
+    match director
+        .requests
+        .iter_mut()
+        .find(|(id, _)| *id == TypeId::of::<A>())
+    {
+        Some((_, requested)) => *requested = playlist,
+        None => director.requests.push((TypeId::of::<A>(), playlist)),
+    }
+}
+
+fn release_music<A: MusicCollection>(mut director: ResMut<MusicDirector>) {
+    director.requests.retain(|(id, _)| *id != TypeId::of::<A>());
+}
+
+/// A stem spawned by the [`MusicDirector`].
+#[derive(Component, Debug)]
+struct MusicTrack {
+    /// The crossfade volume, shared by every stem of the track.
+    volume: f32,
+    fading_out: bool,
+    stem: Stem,
+    /// The volume of the stem at the current intensity.
+    stem_volume: f32,
+}
+
+fn spawn_track(
+    commands: &mut Commands,
+    playlist: &Playlist,
+    index: usize,
+    volume: f32,
+    intensity: f32,
+) {
+    // A lone track loops, the tracks of a longer playlist are queued one by one.
+    let repeat_mode = if playlist.0.len() == 1 {
+        RepeatMode::RepeatEndlessly
+    } else {
+        RepeatMode::PlayOnce
+    };
+    for stem in &playlist.0[index].0 {
+        let stem_volume = stem.gain(intensity);
+        commands.spawn((
+            Name::new("Music"),
+            MusicTrack {
+                volume,
+                fading_out: false,
+                stem: stem.clone(),
+                stem_volume,
+            },
+            SamplePlayer {
+                sample: stem.sample.clone(),
+                repeat_mode,
+                ..default()
+            },
+            MusicPool,
+            sample_effects![VolumeNode {
+                volume: Volume::Linear(volume * stem_volume),
+                ..default()
+            }],
+        ));
+    }
+}
+
+fn update_music(
+    mut commands: Commands,
+    mut director: ResMut<MusicDirector>,
+    intensity: Res<MusicIntensity>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    let requested = director
+        .requests
+        .last()
+        .map(|(_, playlist)| playlist.clone())
+        .filter(|playlist| !playlist.0.is_empty());
+
+    if requested != director.current {
+        for mut track in &mut tracks {
+            track.fading_out = true;
+        }
+        if let Some(playlist) = &requested {
+            spawn_track(&mut commands, playlist, 0, 0.0, intensity.0);
+        }
+        director.current = requested;
+        director.track = 0;
+        return;
+    }
+
+    // Playing samples are despawned once they are done, queue the next one of the playlist.
+    let Some(playlist) = &director.current else {
+        return;
+    };
+    if playlist.0.len() > 1 && tracks.iter().all(|track| track.fading_out) {
+        let next = (director.track + 1) % playlist.0.len();
+        spawn_track(&mut commands, playlist, next, 1.0, intensity.0);
+        director.track = next;
+    }
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    intensity: Res<MusicIntensity>,
+    mut tracks: Query<(Entity, &mut MusicTrack, &SampleEffects)>,
+    mut volume_nodes: Query<&mut VolumeNode>,
+) {
+    let delta = time.delta_secs();
+    let step = delta / CROSSFADE_SECS;
+    for (entity, mut track, effects) in &mut tracks {
+        if track.fading_out {
+            track.volume = (track.volume - step).max(0.0);
+            if track.volume == 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        } else if track.volume < 1.0 {
+            track.volume = (track.volume + step).min(1.0);
+        }
+        let target = track.stem.gain(intensity.0);
+        track
+            .stem_volume
+            .smooth_nudge(&target, STEM_DECAY_RATE, delta);
+
+        let volume = Volume::Linear(track.volume * track.stem_volume);
+        if let Ok(mut node) = volume_nodes.get_effect_mut(effects)
+            && node.volume != volume
+        {
+            node.volume = volume;
+        }
+    }
+}
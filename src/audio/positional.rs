@@ -0,0 +1,77 @@
+//! Sound effects panned and attenuated by their horizontal position relative to the camera.
+
+use bevy::prelude::*;
+use bevy_seedling::{
+    pool::SamplerPool,
+    prelude::{EffectsQuery, PoolLabel, SampleEffects, Volume, VolumePanNode},
+    sample::{AudioSample, SamplePlayer},
+    sample_effects,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_positional_pool);
+    app.add_systems(
+        PostUpdate,
+        pan_positional_sounds.after(TransformSystems::Propagate),
+    );
+}
+
+/// How far a sound can be panned to either side, a full pan sounds unnatural in headphones.
+const MAX_PAN: f32 = 0.8;
+/// How much quieter a sound at the edge of the screen is than one in the middle.
+const EDGE_ATTENUATION: f32 = 0.5;
+/// The quietest an off-screen sound can get.
+const MIN_GAIN: f32 = 0.2;
+
+#[derive(PoolLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct PositionalSoundPool;
+
+fn spawn_positional_pool(mut cmd: Commands) {
+    cmd.spawn((
+        Name::new("PositionalSoundPool"),
+        SamplerPool(PositionalSoundPool),
+        sample_effects![VolumePanNode::default()],
+    ));
+}
+
+/// A sound effect emitted at a world `position`, with a linear volume.
+pub fn positional_sound_effect(
+    handle: Handle<AudioSample>,
+    volume: f32,
+    position: Vec3,
+) -> impl Bundle {
+    (
+        SamplePlayer {
+            volume: Volume::Linear(volume),
+            ..SamplePlayer::new(handle)
+        },
+        PositionalSoundPool,
+        Transform::from_translation(position),
+    )
+}
+
+fn pan_positional_sounds(
+    camera: Single<(&GlobalTransform, &Projection), With<Camera2d>>,
+    sounds: Query<(&GlobalTransform, &SampleEffects), With<PositionalSoundPool>>,
+    mut pan_nodes: Query<&mut VolumePanNode>,
+) {
+    let (camera_transform, projection) = *camera;
+    let half_width = match projection {
+        Projection::Orthographic(ortho) => ortho.area.width() / 2.0,
+        _ => return,
+    };
+    if half_width <= 0.0 {
+        return;
+    }
+
+    for (transform, effects) in &sounds {
+        // -1 and 1 are the left and right edges of the screen.
+        let offset = (transform.translation().x - camera_transform.translation().x) / half_width;
+        let pan = offset.clamp(-1.0, 1.0) * MAX_PAN;
+        let gain = (1.0 - EDGE_ATTENUATION * offset.abs()).max(MIN_GAIN);
+        if let Ok(mut node) = pan_nodes.get_effect_mut(effects) {
+            node.pan = pan;
+            node.volume = Volume::Linear(gain);
+        }
+    }
+}
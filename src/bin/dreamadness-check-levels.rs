@@ -0,0 +1,290 @@
+//! Checks the LDtk projects for mistakes the game would otherwise silently ignore: entities that
+//! aren't registered in the game, tilesets that can't be found, and entities placed outside of
+//! their level.
+//!
+//! ```text
+//! cargo run --bin dreamadness-check-levels
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use bevy::math::IVec2;
+use bevy_ecs_ldtk::ldtk::{LdtkJson, Level};
+use dreamadness::game::fish_level::ENTITIES;
+
+const USAGE: &str = "\
+Usage: dreamadness-check-levels [OPTIONS]
+
+Options:
+  --levels <DIR>  Folder of the LDtk projects to check [default: assets/levels]
+  -h, --help      Print this help";
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut projects = Vec::new();
+    if let Err(e) = find_projects(&options.levels, &mut projects) {
+        eprintln!("error: can't read {}: {e}", options.levels.display());
+        return ExitCode::FAILURE;
+    }
+    projects.sort();
+
+    let (mut errors, mut warnings) = (0, 0);
+    for project in &projects {
+        for issue in check_project(project) {
+            match issue.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            eprintln!(
+                "{}: {}: {}",
+                issue.severity,
+                project.display(),
+                issue.message
+            );
+        }
+    }
+    println!(
+        "Checked {} LDtk projects: {errors} errors, {warnings} warnings",
+        projects.len()
+    );
+    if errors > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+struct Options {
+    levels: PathBuf,
+}
+
+impl Options {
+    /// Parses the command line arguments, or returns `None` if help was asked for.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self {
+            levels: PathBuf::from("assets/levels"),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--levels" => {
+                    options.levels = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for `{arg}`"))?
+                        .into();
+                }
+                _ => return Err(format!("unknown option `{arg}`")),
+            }
+        }
+        Ok(Some(options))
+    }
+}
+
+/// Collects the `.ldtk` files in `dir` and its subfolders.
+fn find_projects(dir: &Path, projects: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_projects(&path, projects)?;
+        } else if path.extension().is_some_and(|ext| ext == "ldtk") {
+            projects.push(path);
+        }
+    }
+    Ok(())
+}
+
+enum Severity {
+    /// Something the game gets wrong.
+    Error,
+    /// Something that is likely a mistake, but doesn't affect the game yet.
+    Warning,
+}
+
+struct Issue {
+    severity: Severity,
+    message: String,
+}
+
+impl Issue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+fn check_project(path: &Path) -> Vec<Issue> {
+    let project: LdtkJson = match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| serde_json::from_str(&contents).map_err(|e| e.to_string()))
+    {
+        Ok(project) => project,
+        Err(e) => return vec![Issue::error(format!("can't parse the project: {e}"))],
+    };
+    // Paths in the project are relative to it.
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut issues = Vec::new();
+
+    let mut levels = Vec::new();
+    for level in project
+        .levels
+        .iter()
+        .chain(project.worlds.iter().flat_map(|world| &world.levels))
+    {
+        match (&level.layer_instances, &level.external_rel_path) {
+            (None, Some(external)) => match read_external_level(&dir.join(external)) {
+                Ok(level) => levels.push(level),
+                Err(e) => issues.push(Issue::error(format!(
+                    "can't read the level {} from {external}: {e}",
+                    level.identifier
+                ))),
+            },
+            _ => levels.push(level.clone()),
+        }
+    }
+
+    // Entities.
+    let registered: HashSet<_> = ENTITIES.iter().copied().collect();
+    let mut placed = HashSet::new();
+    for level in &levels {
+        for entity in level
+            .layer_instances
+            .iter()
+            .flatten()
+            .flat_map(|layer| &layer.entity_instances)
+        {
+            placed.insert(entity.identifier.as_str());
+            if !registered.contains(entity.identifier.as_str()) {
+                issues.push(Issue::error(format!(
+                    "{}: {} at {} isn't registered in the game",
+                    level.identifier, entity.identifier, entity.px
+                )));
+            }
+
+            let size = IVec2::new(entity.width, entity.height).as_vec2();
+            let min = entity.px.as_vec2() - entity.pivot * size;
+            let max = min + size;
+            if min.x < 0.0
+                || min.y < 0.0
+                || max.x > level.px_wid as f32
+                || max.y > level.px_hei as f32
+            {
+                issues.push(Issue::error(format!(
+                    "{}: {} at {} is out of the level's {}x{} bounds",
+                    level.identifier, entity.identifier, entity.px, level.px_wid, level.px_hei
+                )));
+            }
+        }
+    }
+    for definition in &project.defs.entities {
+        if !registered.contains(definition.identifier.as_str())
+            && !placed.contains(definition.identifier.as_str())
+        {
+            issues.push(Issue::warning(format!(
+                "{} is defined but isn't registered in the game",
+                definition.identifier
+            )));
+        }
+    }
+
+    // Tilesets.
+    let tilesets: HashSet<_> = project.defs.tilesets.iter().map(|t| t.uid).collect();
+    for tileset in &project.defs.tilesets {
+        // Tilesets without a path are embedded in LDtk, like its icons.
+        if let Some(rel_path) = &tileset.rel_path
+            && !dir.join(rel_path).is_file()
+        {
+            issues.push(Issue::error(format!(
+                "the tileset {} refers to {rel_path}, which doesn't exist",
+                tileset.identifier
+            )));
+        }
+    }
+    let mut references = Vec::new();
+    for definition in &project.defs.entities {
+        references.push((
+            format!("the entity {}", definition.identifier),
+            definition.tileset_id,
+        ));
+    }
+    for definition in &project.defs.layers {
+        references.push((
+            format!("the layer {}", definition.identifier),
+            definition.tileset_def_uid,
+        ));
+    }
+    for level in &levels {
+        for layer in level.layer_instances.iter().flatten() {
+            references.push((
+                format!("{}: the layer {}", level.identifier, layer.identifier),
+                layer.override_tileset_uid.or(layer.tileset_def_uid),
+            ));
+            for entity in &layer.entity_instances {
+                references.push((
+                    format!(
+                        "{}: {} at {}",
+                        level.identifier, entity.identifier, entity.px
+                    ),
+                    entity.tile.as_ref().map(|tile| tile.tileset_uid),
+                ));
+            }
+        }
+        if let Some(bg_rel_path) = &level.bg_rel_path
+            && !dir.join(bg_rel_path).is_file()
+        {
+            issues.push(Issue::error(format!(
+                "{}: the background {bg_rel_path} doesn't exist",
+                level.identifier
+            )));
+        }
+    }
+    for (user, uid) in references {
+        if let Some(uid) = uid
+            && !tilesets.contains(&uid)
+        {
+            issues.push(Issue::error(format!(
+                "{user} uses the missing tileset {uid}"
+            )));
+        }
+    }
+
+    issues
+}
+
+fn read_external_level(path: &Path) -> Result<Level, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&contents).map_err(|e| e.to_string())
+}
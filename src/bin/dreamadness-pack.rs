@@ -0,0 +1,90 @@
+//! Packs the `assets` folder into a single archive, read by release builds instead of the loose
+//! files when it sits next to the executable:
+//!
+//! ```text
+//! cargo run --bin dreamadness-pack -- --output dist/assets.pak
+//! ```
+
+use std::{path::PathBuf, process::ExitCode};
+
+use dreamadness::archive::{self, ARCHIVE_NAME};
+
+const USAGE: &str = "\
+Usage: dreamadness-pack [OPTIONS]
+
+Options:
+  --input <DIR>    Folder to pack [default: assets]
+  --output <PATH>  Archive to write [default: assets.pak]
+  --no-compress    Store every file as is
+  -h, --help       Print this help";
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match archive::pack(&options.input, &options.output, options.compress) {
+        Ok(stats) => {
+            println!(
+                "Packed {} files ({} compressed) into {}: {} bytes, {} before compression",
+                stats.files,
+                stats.compressed,
+                options.output.display(),
+                stats.stored_size,
+                stats.size,
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!(
+                "error: can't pack {} into {}: {e}",
+                options.input.display(),
+                options.output.display()
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct Options {
+    input: PathBuf,
+    output: PathBuf,
+    compress: bool,
+}
+
+impl Options {
+    /// Parses the command line arguments, or returns `None` if help was asked for.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self {
+            input: PathBuf::from("assets"),
+            output: PathBuf::from(ARCHIVE_NAME),
+            compress: true,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--no-compress" => options.compress = false,
+                "--input" | "--output" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for `{arg}`"))?;
+                    if arg == "--input" {
+                        options.input = value.into();
+                    } else {
+                        options.output = value.into();
+                    }
+                }
+                _ => return Err(format!("unknown option `{arg}`")),
+            }
+        }
+        Ok(Some(options))
+    }
+}
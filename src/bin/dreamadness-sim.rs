@@ -0,0 +1,565 @@
+//! Headless balance simulation of the fish level.
+//!
+//! Plays the level with a bot for a number of seeds and prints one line of statistics per
+//! run, as CSV or JSON:
+//!
+//! ```text
+//! cargo run --bin dreamadness-sim -- --seeds 20 --strategy lead --format json
+//! ```
+
+use std::{collections::HashSet, fmt::Write as _, process::ExitCode, str::FromStr};
+
+use bevy::{
+    input::{ButtonState, mouse::MouseButtonInput},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_ecs_ldtk::{LevelEvent, assets::LdtkProject, prelude::LdtkProjectHandle};
+use dreamadness::{
+    AppPlugin,
+    game::{
+        GameRng,
+        fish_level::{
+            Ball, BallSpawnTimer, FishFed, FishSpeed, FishState, FoodWasted, LevelProject, Patrol,
+            PlayerMarker,
+        },
+        level_config::LevelCompleted,
+    },
+    screens::Screen,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+const USAGE: &str = "\
+Usage: dreamadness-sim [OPTIONS]
+
+Options:
+  --level <PATH>         LDtk project to play, relative to `assets/` [default: levels/level_1.ldtk]
+  --seeds <N>            Number of runs [default: 10]
+  --first-seed <SEED>    Seed of the first run, the following runs increment it [default: 0]
+  --strategy <STRATEGY>  Bot strategy: random, below or lead [default: lead]
+  --max-secs <SECS>      Give up on a run after this much game time [default: 300]
+  --format <FORMAT>      Output format: csv or json [default: csv]
+  -h, --help             Print this help";
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins(
+        AppPlugin::new()
+            .headless(true)
+            .editor(false)
+            .dev_tools(false)
+            .start_screen(Screen::Title)
+            .level(options.level.clone()),
+    )
+    // Run exactly one fixed tick per frame, as fast as possible.
+    .insert_resource(TimeUpdateStrategy::FixedTimesteps(1))
+    .insert_resource(Simulation::new(options))
+    .add_systems(Update, start_next_run.run_if(in_state(Screen::Title)))
+    .add_systems(OnEnter(Screen::LoadError), exit_on_load_error)
+    .add_systems(OnExit(Screen::Gameplay), restart_level)
+    .add_systems(
+        Update,
+        (record_events, drive_bot, end_run)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    )
+    // Loading counts too, a level that never loads would keep the simulation waiting.
+    .add_systems(
+        FixedUpdate,
+        (
+            detect_level_spawn.run_if(in_state(Screen::Gameplay)),
+            tick_run,
+        )
+            .chain(),
+    )
+    .add_systems(Update, give_up_on_stuck_run);
+
+    if app.run().is_success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+struct Options {
+    level: String,
+    seeds: u64,
+    first_seed: u64,
+    strategy: Strategy,
+    max_secs: f32,
+    format: Format,
+}
+
+impl Options {
+    /// Returns `None` if help was requested.
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self {
+            level: LevelProject::default().0,
+            seeds: 10,
+            first_seed: 0,
+            strategy: Strategy::Lead,
+            max_secs: 300.0,
+            format: Format::Csv,
+        };
+        while let Some(arg) = args.next() {
+            if arg == "-h" || arg == "--help" {
+                return Ok(None);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{arg}`"))?;
+            match arg.as_str() {
+                "--level" => options.level = value,
+                "--seeds" => options.seeds = parse_value(&arg, &value)?,
+                "--first-seed" => options.first_seed = parse_value(&arg, &value)?,
+                "--strategy" => options.strategy = parse_value(&arg, &value)?,
+                "--max-secs" => options.max_secs = parse_value(&arg, &value)?,
+                "--format" => options.format = parse_value(&arg, &value)?,
+                _ => return Err(format!("unknown option `{arg}`")),
+            }
+        }
+        Ok(Some(options))
+    }
+}
+
+fn parse_value<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for `{arg}`"))
+}
+
+/// Where the bot drops food.
+#[derive(Debug, Clone, Copy)]
+enum Strategy {
+    /// Anywhere in the tank.
+    Random,
+    /// Right above the closest fish that hasn't eaten yet.
+    Below,
+    /// Where the closest fish that hasn't eaten yet will be when the food reaches it.
+    Lead,
+}
+
+impl FromStr for Strategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "below" => Ok(Self::Below),
+            "lead" => Ok(Self::Lead),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Strategy {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Below => "below",
+            Self::Lead => "lead",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Simulation {
+    options: Options,
+    runs_started: u64,
+    run: Option<Run>,
+    results: Vec<RunResult>,
+}
+
+impl Simulation {
+    fn new(options: Options) -> Self {
+        Self {
+            options,
+            runs_started: 0,
+            run: None,
+            results: Vec::new(),
+        }
+    }
+
+    fn report(&self) -> String {
+        let mut out = String::new();
+        match self.options.format {
+            Format::Csv => {
+                out.push_str(RunResult::CSV_HEADER);
+                out.push('\n');
+                for result in &self.results {
+                    let _ = writeln!(out, "{}", result.to_csv());
+                }
+            }
+            Format::Json => {
+                let rows: Vec<_> = self.results.iter().map(RunResult::to_json).collect();
+                let _ = writeln!(out, "[\n  {}\n]", rows.join(",\n  "));
+            }
+        }
+        out
+    }
+}
+
+/// The state of the run in progress.
+struct Run {
+    seed: u64,
+    rng: StdRng,
+    /// Whether the level has been spawned.
+    spawned: bool,
+    /// The width of the spawned level, which the player and the fish move across.
+    tank_width: f32,
+    /// Whether the game sent [`LevelCompleted`].
+    completed: bool,
+    /// Fixed ticks spent waiting for the level to spawn, since it last did.
+    loading_ticks: u64,
+    /// Fixed ticks since the level was spawned.
+    ticks: u64,
+    /// How far from the target the bot drops its next food ball, like a player's shaky hand.
+    aim_offset: f32,
+    fish_fed: HashSet<Entity>,
+    feedings: u32,
+    food_dropped: u32,
+    food_wasted: u32,
+}
+
+struct RunResult {
+    seed: u64,
+    strategy: Strategy,
+    completed: bool,
+    time_secs: f32,
+    fish_fed: usize,
+    fish_total: usize,
+    feedings: u32,
+    food_dropped: u32,
+    food_wasted: u32,
+}
+
+impl RunResult {
+    const CSV_HEADER: &str =
+        "seed,strategy,completed,time_secs,fish_fed,fish_total,feedings,food_dropped,food_wasted";
+
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{:.3},{},{},{},{},{}",
+            self.seed,
+            self.strategy.name(),
+            self.completed,
+            self.time_secs,
+            self.fish_fed,
+            self.fish_total,
+            self.feedings,
+            self.food_dropped,
+            self.food_wasted,
+        )
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            r#"{{"seed": {}, "strategy": "{}", "completed": {}, "time_secs": {:.3}, "fish_fed": {}, "fish_total": {}, "feedings": {}, "food_dropped": {}, "food_wasted": {}}}"#,
+            self.seed,
+            self.strategy.name(),
+            self.completed,
+            self.time_secs,
+            self.fish_fed,
+            self.fish_total,
+            self.feedings,
+            self.food_dropped,
+            self.food_wasted,
+        )
+    }
+}
+
+fn start_next_run(
+    mut commands: Commands,
+    mut sim: ResMut<Simulation>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    if sim.runs_started == sim.options.seeds {
+        print!("{}", sim.report());
+        app_exit.write(AppExit::Success);
+        return;
+    }
+
+    let seed = sim.options.first_seed + sim.runs_started;
+    sim.runs_started += 1;
+    // The seed makes the bot play differently. The game gets it too, though none of its
+    // gameplay is random yet.
+    commands.insert_resource(GameRng::from_seed(seed));
+    let mut rng = StdRng::seed_from_u64(seed);
+    sim.run = Some(Run {
+        seed,
+        aim_offset: aim_offset(&mut rng),
+        rng,
+        spawned: false,
+        tank_width: 0.0,
+        completed: false,
+        loading_ticks: 0,
+        ticks: 0,
+        fish_fed: HashSet::new(),
+        feedings: 0,
+        food_dropped: 0,
+        food_wasted: 0,
+    });
+    // The loading screen loads the gameplay assets, then goes on to gameplay.
+    next_screen.set(Screen::Loading);
+}
+
+/// The game has logged what failed, there is nothing to simulate without it.
+fn exit_on_load_error(mut app_exit: MessageWriter<AppExit>) {
+    app_exit.write(AppExit::error());
+}
+
+/// The game restarts a level that ran out of time through the loading screen, the run goes on
+/// with the fresh level.
+fn restart_level(mut sim: ResMut<Simulation>) {
+    if let Some(run) = &mut sim.run {
+        run.spawned = false;
+        run.fish_fed.clear();
+    }
+}
+
+fn detect_level_spawn(
+    mut sim: ResMut<Simulation>,
+    mut level_events: MessageReader<LevelEvent>,
+    level: Query<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+) {
+    let Some(run) = &mut sim.run else {
+        return;
+    };
+    for event in level_events.read() {
+        let LevelEvent::Spawned(iid) = event else {
+            continue;
+        };
+        run.spawned = true;
+        run.loading_ticks = 0;
+        if let Some(raw_level) = level
+            .iter()
+            .filter_map(|handle| projects.get(handle.id()))
+            .flat_map(|project| &project.json_data().levels)
+            .find(|raw_level| raw_level.iid == *iid.get())
+        {
+            run.tank_width = raw_level.px_wid as f32;
+        }
+    }
+}
+
+/// Bails out of the simulation if a run never gets to play, e.g. stuck on the loading screen.
+fn give_up_on_stuck_run(
+    sim: Res<Simulation>,
+    time: Res<Time<Fixed>>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    let Some(run) = &sim.run else {
+        return;
+    };
+    let loading_secs = run.loading_ticks as f32 * time.timestep().as_secs_f32();
+    if loading_secs >= sim.options.max_secs {
+        eprintln!(
+            "error: run {} didn't start playing in {}s",
+            run.seed, sim.options.max_secs
+        );
+        print!("{}", sim.report());
+        app_exit.write(AppExit::error());
+    }
+}
+
+fn tick_run(mut sim: ResMut<Simulation>) {
+    // Don't count the time spent loading the level.
+    if let Some(run) = &mut sim.run {
+        if run.spawned {
+            run.ticks += 1;
+        } else {
+            run.loading_ticks += 1;
+        }
+    }
+}
+
+fn record_events(
+    mut sim: ResMut<Simulation>,
+    mut fed: MessageReader<FishFed>,
+    mut wasted: MessageReader<FoodWasted>,
+    mut completed: MessageReader<LevelCompleted>,
+    dropped: Query<(), Added<Ball>>,
+) {
+    let Some(run) = &mut sim.run else {
+        return;
+    };
+    run.completed |= completed.read().count() > 0;
+    for FishFed { fish } in fed.read() {
+        run.fish_fed.insert(*fish);
+        run.feedings += 1;
+    }
+    run.food_wasted += wasted.read().count() as u32;
+    run.food_dropped += dropped.iter().count() as u32;
+}
+
+fn drive_bot(
+    mut sim: ResMut<Simulation>,
+    player: Single<(&mut Transform, &GlobalTransform, &BallSpawnTimer), With<PlayerMarker>>,
+    fish: Query<
+        (
+            Entity,
+            &FishState,
+            &FishSpeed,
+            &Patrol,
+            &Transform,
+            &GlobalTransform,
+        ),
+        Without<PlayerMarker>,
+    >,
+    mut mouse_button_input: MessageWriter<MouseButtonInput>,
+) {
+    let strategy = sim.options.strategy;
+    let Some(run) = &mut sim.run else {
+        return;
+    };
+    let (mut player_tf, player_gtf, spawn_timer) = player.into_inner();
+    if !spawn_timer.0.is_finished() {
+        return;
+    }
+
+    // The player and the fish both move in the level's space, between 0 and its width.
+    let tank_width = run.tank_width;
+    if tank_width <= 0.0 {
+        return;
+    }
+    let drop_height = player_gtf.translation().y;
+    let target = match strategy {
+        Strategy::Random => Some(run.rng.random_range(0.0..tank_width)),
+        Strategy::Below | Strategy::Lead => fish
+            .iter()
+            .filter(|(entity, state, ..)| !is_eating(**state) && !run.fish_fed.contains(entity))
+            .map(|(_, state, speed, patrol, tf, gtf)| {
+                let x = tf.translation.x;
+                match strategy {
+                    Strategy::Lead => {
+                        let ticks = drop_height - gtf.translation().y;
+                        predict_x(x, fish_speed(*state, speed), ticks, patrol)
+                    }
+                    _ => x,
+                }
+            })
+            .min_by(|a, b| {
+                let distance = |x: f32| (x - player_tf.translation.x).abs();
+                distance(*a).total_cmp(&distance(*b))
+            }),
+    };
+    let Some(target) = target.map(|x| (x + run.aim_offset).clamp(0.0, tank_width)) else {
+        return;
+    };
+
+    // The food is dropped from the player's global position, which is only updated at the end
+    // of the frame, so move first and drop once the player is close enough to the target.
+    let aimed = (player_tf.translation.x - target).abs() <= AIM_TOLERANCE;
+    player_tf.translation.x = target;
+    if !aimed {
+        return;
+    }
+    mouse_button_input.write(MouseButtonInput {
+        button: MouseButton::Left,
+        state: ButtonState::Pressed,
+        window: Entity::PLACEHOLDER,
+    });
+    run.aim_offset = aim_offset(&mut run.rng);
+}
+
+/// Picks how far off the next ball is dropped, so that each seed plays differently.
+fn aim_offset(rng: &mut StdRng) -> f32 {
+    rng.random_range(-AIM_JITTER..=AIM_JITTER)
+}
+
+fn end_run(
+    mut sim: ResMut<Simulation>,
+    fish: Query<(), With<FishState>>,
+    time: Res<Time<Fixed>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let max_secs = sim.options.max_secs;
+    let strategy = sim.options.strategy;
+    let Some(run) = &sim.run else {
+        return;
+    };
+    let fish_total = fish.iter().count();
+    let time_secs = run.ticks as f32 * time.timestep().as_secs_f32();
+    let completed = run.completed;
+    // Give up on levels that have no fish to feed, they would never complete.
+    let stuck = run.ticks > 0 && fish_total == 0;
+    if !completed && !stuck && time_secs < max_secs {
+        return;
+    }
+
+    let result = RunResult {
+        seed: run.seed,
+        strategy,
+        completed,
+        time_secs,
+        fish_fed: run.fish_fed.len(),
+        fish_total,
+        feedings: run.feedings,
+        food_dropped: run.food_dropped,
+        food_wasted: run.food_wasted,
+    };
+    sim.results.push(result);
+    sim.run = None;
+    // Leaving gameplay despawns the level, the next run spawns a fresh one.
+    next_screen.set(Screen::Title);
+}
+
+/// How far from its target the player can drop food, in pixels. Fish catch food within 16.
+const AIM_TOLERANCE: f32 = 4.0;
+/// How far from its target the bot aims, in pixels.
+const AIM_JITTER: f32 = 4.0;
+
+fn is_eating(state: FishState) -> bool {
+    matches!(state, FishState::EatingLeft | FishState::EatingRight)
+}
+
+/// Horizontal speed of a fish, in pixels per fixed tick.
+fn fish_speed(state: FishState, speed: &FishSpeed) -> f32 {
+    match state {
+        FishState::SlowLeft => -speed.slow,
+        FishState::FastLeft => -speed.fast,
+        FishState::SlowRight => speed.slow,
+        FishState::FastRight => speed.fast,
+        FishState::Idle | FishState::EatingLeft | FishState::EatingRight => 0.0,
+    }
+}
+
+/// Where a fish at `x` will be after `ticks`, bouncing off the ends of its patrol.
+fn predict_x(x: f32, speed: f32, ticks: f32, patrol: &Patrol) -> f32 {
+    let width = patrol.max - patrol.min;
+    let x = (x - patrol.min + speed * ticks.max(0.0)).rem_euclid(2.0 * width);
+    let x = if x > width { 2.0 * width - x } else { x };
+    patrol.min + x
+}
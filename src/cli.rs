@@ -0,0 +1,155 @@
+//! Command-line launch options, parsed before the [`App`](bevy::prelude::App) is created.
+
+use bevy::prelude::*;
+
+use crate::{
+    AppPlugin,
+    game::{checksum::ChecksumLog, replay::Replay},
+    screens::Screen,
+};
+
+pub const USAGE: &str = "\
+Usage: dreamadness [OPTIONS]
+
+Options:
+  --skip-splash              Start on the title screen
+  --level <NAME|PATH>        Start playing a level, either a name from `assets/levels` or an
+                             LDtk project path relative to `assets/`
+  --editor                   Start in the editor
+  --seed <SEED>              Seed the gameplay random number generator
+  --window-size <WIDTHxHEIGHT>
+                             Initial window size in logical pixels, e.g. 1280x720
+  --fullscreen               Start in borderless fullscreen
+  --record-checksums <PATH>  Record per-tick gameplay checksums to a file
+  --verify-checksums <PATH>  Compare per-tick gameplay checksums with a recorded file
+  --record-replay <PATH>     Record the player's input to a file
+  --replay <PATH>            Play a recorded replay on the level it was recorded on
+  -h, --help                 Print this help";
+
+/// How the game was asked to start.
+#[derive(Debug, Default)]
+pub struct LaunchOptions {
+    pub skip_splash: bool,
+    pub level: Option<String>,
+    pub editor: bool,
+    pub seed: Option<u64>,
+    pub window_size: Option<UVec2>,
+    pub fullscreen: bool,
+    pub record_checksums: Option<String>,
+    pub verify_checksums: Option<String>,
+    pub record_replay: Option<String>,
+    pub replay: Option<String>,
+}
+
+impl LaunchOptions {
+    /// Parses the arguments, without the program name. Returns `None` if help was requested.
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{arg}`"))
+            };
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--skip-splash" => options.skip_splash = true,
+                "--level" => options.level = Some(level_path(&value()?)),
+                "--editor" => options.editor = true,
+                "--seed" => {
+                    let value = value()?;
+                    options.seed = Some(value.parse().map_err(|_| invalid_value(&arg, &value))?);
+                }
+                "--window-size" => {
+                    let value = value()?;
+                    options.window_size =
+                        Some(parse_size(&value).ok_or_else(|| invalid_value(&arg, &value))?);
+                }
+                "--fullscreen" => options.fullscreen = true,
+                "--record-checksums" => options.record_checksums = Some(value()?),
+                "--verify-checksums" => options.verify_checksums = Some(value()?),
+                "--record-replay" => options.record_replay = Some(value()?),
+                "--replay" => options.replay = Some(value()?),
+                _ => return Err(format!("unknown option `{arg}`")),
+            }
+        }
+        Ok(Some(options))
+    }
+
+    /// The [`AppPlugin`] that starts the game as requested.
+    pub fn app_plugin(&self) -> Result<AppPlugin, String> {
+        let replay = self
+            .replay
+            .as_ref()
+            .map(|path| {
+                Replay::play(path).map_err(|e| format!("failed to read the replay `{path}`: {e}"))
+            })
+            .transpose()?;
+        // A replay is played on the level it was recorded on.
+        let level = replay
+            .as_ref()
+            .map(|replay| replay.level().to_string())
+            .or_else(|| self.level.clone());
+
+        let start_screen = if self.editor {
+            #[cfg(feature = "editor")]
+            {
+                Screen::Editor
+            }
+            #[cfg(not(feature = "editor"))]
+            return Err("the editor isn't included in this build".to_string());
+        } else if level.is_some() {
+            // Go through the loading screen so the level starts with its assets ready.
+            Screen::Loading
+        } else if self.skip_splash {
+            Screen::Title
+        } else {
+            Screen::Splash
+        };
+
+        let mut plugin = AppPlugin::new()
+            .start_screen(start_screen)
+            .fullscreen(self.fullscreen);
+        if let Some(level) = level {
+            plugin = plugin.level(level);
+        }
+        if let Some(seed) = self.seed {
+            plugin = plugin.seed(seed);
+        }
+        if let Some(size) = self.window_size {
+            plugin = plugin.window_size(size);
+        }
+        if let Some(path) = &self.record_checksums {
+            plugin = plugin.checksum_log(ChecksumLog::record(path));
+        }
+        if let Some(path) = &self.verify_checksums {
+            let log = ChecksumLog::compare(path)
+                .map_err(|e| format!("failed to read checksums from `{path}`: {e}"))?;
+            plugin = plugin.checksum_log(log);
+        }
+        if let Some(path) = &self.record_replay {
+            plugin = plugin.replay(Replay::record(path));
+        }
+        if let Some(replay) = replay {
+            plugin = plugin.replay(replay);
+        }
+        Ok(plugin)
+    }
+}
+
+/// Levels can be named after their file in `assets/levels`, e.g. `level_1`.
+fn level_path(level: &str) -> String {
+    if level.ends_with(".ldtk") {
+        level.to_string()
+    } else {
+        format!("levels/{level}.ldtk")
+    }
+}
+
+fn parse_size(value: &str) -> Option<UVec2> {
+    let (width, height) = value.split_once('x')?;
+    Some(UVec2::new(width.parse().ok()?, height.parse().ok()?))
+}
+
+fn invalid_value(arg: &str, value: &str) -> String {
+    format!("invalid value `{value}` for `{arg}`")
+}
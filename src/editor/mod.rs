@@ -1,11 +1,11 @@
 use bevy::{
     asset::{ReflectAsset, UntypedAssetId},
     color::palettes::tailwind::*,
-    picking::pointer::{PointerAction, PointerInput, PointerInteraction},
+    picking::pointer::PointerInteraction,
     prelude::*,
 };
-use bevy_camera::{Viewport, visibility::RenderLayers};
-use bevy_egui::{EguiGlobalSettings, EguiPrimaryContextPass, PrimaryEguiContext, egui};
+use bevy_camera::Viewport;
+use bevy_egui::{EguiPrimaryContextPass, PrimaryEguiContext, egui};
 use bevy_inspector_egui::DefaultInspectorConfigPlugin;
 use bevy_inspector_egui::bevy_egui::{EguiContext, EguiContextSettings};
 use bevy_inspector_egui::bevy_inspector::hierarchy::{SelectedEntities, hierarchy_ui};
@@ -13,14 +13,12 @@ use bevy_inspector_egui::bevy_inspector::{
     self, ui_for_entities_shared_components, ui_for_entity_with_children,
 };
 use bevy_inspector_egui::egui::LayerId;
-use bevy_pancam::{PanCam, PanCamClampBounds};
 use bevy_reflect::TypeRegistry;
 use bevy_window::{PrimaryWindow, Window};
 use egui_dock::{DockArea, DockState, NodeIndex, Style};
 use std::any::TypeId;
-use transform_gizmo_bevy::{GizmoCamera, GizmoTarget, TransformGizmoPlugin};
 
-use crate::{asset_tracking::ResourceHandles, screens::Screen};
+use crate::screens::Screen;
 
 pub(super) fn plugin(app: &mut App) {
     app
@@ -285,7 +283,7 @@ fn select_resource(
             )
         })
         .collect();
-    resources.sort_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b));
+    resources.sort_by_key(|(name, _)| *name);
 
     for (resource_name, type_id) in resources {
         let selected = match *selection {
@@ -316,7 +314,7 @@ fn select_asset(
             ))
         })
         .collect();
-    assets.sort_by(|(name_a, ..), (name_b, ..)| name_a.cmp(name_b));
+    assets.sort_by_key(|(name, ..)| *name);
 
     for (asset_name, asset_type_id, reflect_asset) in assets {
         let handles: Vec<_> = reflect_asset.ids(world).collect();
@@ -341,16 +339,12 @@ fn select_asset(
 }
 
 fn enable_editor(
-    resource_handles: Res<ResourceHandles>,
     mut next_screen: ResMut<NextState<Screen>>,
     mut editor_toggle: ResMut<NextState<EditorToggle>>,
 ) {
     editor_toggle.set(EditorToggle::Enabled);
-    if resource_handles.is_all_done() {
-        next_screen.set(Screen::Gameplay);
-    } else {
-        next_screen.set(Screen::Loading);
-    }
+    // The loading screen goes straight through to gameplay if its assets are loaded.
+    next_screen.set(Screen::Loading);
 }
 
 fn disable_editor(mut editor_toggle: ResMut<NextState<EditorToggle>>) {
@@ -0,0 +1,276 @@
+//! Per-tick checksums of the gameplay state, used to verify determinism.
+//!
+//! When a [`ChecksumLog`] resource is present, the gameplay state is hashed after every
+//! fixed tick. The log either records the checksums to a file, or compares them against a
+//! previously recorded file and reports the first tick where the two runs diverge.
+//!
+//! Set `DREAMADNESS_CHECKSUM_RECORD` or `DREAMADNESS_CHECKSUM_COMPARE` to a file path to
+//! enable it without touching the code.
+
+use std::{
+    env, fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        fish_level::{AlgaTimer, Ball, BallSpawnTimer, FishState, FishTimer},
+        level_config::LevelProgress,
+    },
+    save::LevelAttempt,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    if !app.world().contains_resource::<ChecksumLog>()
+        && let Some(log) = ChecksumLog::from_env()
+    {
+        app.insert_resource(log);
+    }
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_checksum_log)
+        // After the progress and the score are tracked in `FixedPostUpdate`.
+        .add_systems(
+            FixedLast,
+            hash_gameplay_state
+                .run_if(in_state(Screen::Gameplay).and(resource_exists::<ChecksumLog>)),
+        )
+        .add_systems(
+            OnExit(Screen::Gameplay),
+            write_checksum_log.run_if(resource_exists::<ChecksumLog>),
+        )
+        .add_systems(
+            Last,
+            write_checksum_log.run_if(resource_exists::<ChecksumLog>.and(on_message::<AppExit>)),
+        );
+}
+
+/// What a [`ChecksumLog`] does with the checksums it computes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumMode {
+    /// Write every checksum to the log file.
+    Record,
+    /// Compare every checksum with the ones read from the log file.
+    Compare,
+}
+
+/// The checksums of every fixed tick since [`Screen::Gameplay`] was entered.
+#[derive(Resource, Debug, Clone)]
+pub struct ChecksumLog {
+    mode: ChecksumMode,
+    path: PathBuf,
+    checksums: Vec<u64>,
+    expected: Vec<u64>,
+    divergence: Option<u64>,
+}
+
+impl ChecksumLog {
+    /// Records the checksums to `path` when leaving gameplay or exiting the app.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: ChecksumMode::Record,
+            path: path.into(),
+            checksums: Vec::new(),
+            expected: Vec::new(),
+            divergence: None,
+        }
+    }
+
+    /// Compares the checksums with a log previously written by [`ChecksumLog::record`].
+    pub fn compare(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let expected = read_checksums(&path)?;
+        Ok(Self {
+            mode: ChecksumMode::Compare,
+            path,
+            checksums: Vec::new(),
+            expected,
+            divergence: None,
+        })
+    }
+
+    fn from_env() -> Option<Self> {
+        if let Some(path) = env::var_os(RECORD_ENV_VAR) {
+            return Some(Self::record(path));
+        }
+        let path = env::var_os(COMPARE_ENV_VAR)?;
+        match Self::compare(&path) {
+            Ok(log) => Some(log),
+            Err(e) => {
+                error!("Failed to read checksums from {}: {e}", path.display());
+                None
+            }
+        }
+    }
+}
+
+const RECORD_ENV_VAR: &str = "DREAMADNESS_CHECKSUM_RECORD";
+const COMPARE_ENV_VAR: &str = "DREAMADNESS_CHECKSUM_COMPARE";
+
+fn read_checksums(path: &Path) -> io::Result<Vec<u64>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let checksum = line.split_whitespace().last().unwrap_or_default();
+            u64::from_str_radix(checksum, 16)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+fn reset_checksum_log(log: Option<ResMut<ChecksumLog>>) {
+    if let Some(mut log) = log {
+        log.checksums.clear();
+        log.divergence = None;
+    }
+}
+
+fn hash_gameplay_state(
+    mut log: ResMut<ChecksumLog>,
+    fish: Query<(Entity, &Transform, &FishState, &FishTimer)>,
+    balls: Query<&Transform, With<Ball>>,
+    players: Query<(&Transform, &BallSpawnTimer)>,
+    algae: Query<&AlgaTimer>,
+    progress: Res<LevelProgress>,
+    attempt: Option<Res<LevelAttempt>>,
+) {
+    // Entity ids and query order aren't stable between runs, so every entity is hashed on
+    // its own and the digests are sorted before being combined.
+    let mut digests = Vec::new();
+    digests.extend(fish.iter().map(|(entity, tf, state, timer)| {
+        digest(|h| {
+            "fish".hash(h);
+            hash_transform(tf, h);
+            h.write_u8(*state as u8);
+            hash_timer(&timer.0, h);
+            progress.fed_fish().contains(&entity).hash(h);
+        })
+    }));
+    digests.extend(balls.iter().map(|tf| {
+        digest(|h| {
+            "ball".hash(h);
+            hash_transform(tf, h);
+        })
+    }));
+    digests.extend(players.iter().map(|(tf, timer)| {
+        digest(|h| {
+            "player".hash(h);
+            hash_transform(tf, h);
+            hash_timer(&timer.0, h);
+        })
+    }));
+    digests.extend(algae.iter().map(|timer| {
+        digest(|h| {
+            "alga".hash(h);
+            hash_timer(&timer.0, h);
+        })
+    }));
+    // The score: the progress towards the objective, and the food wasted on the way.
+    digests.push(digest(|h| {
+        "progress".hash(h);
+        progress.hash_progress(h);
+        attempt.as_deref().hash(h);
+    }));
+    digests.sort_unstable();
+    let checksum = digest(|h| digests.iter().for_each(|d| h.write_u64(*d)));
+
+    let tick = log.checksums.len() as u64;
+    log.checksums.push(checksum);
+    if log.mode == ChecksumMode::Compare && log.divergence.is_none() {
+        let expected = log.expected.get(tick as usize).copied();
+        if expected != Some(checksum) {
+            error!(
+                "Gameplay diverged at tick {tick}: expected {expected:016x?}, got {checksum:016x}"
+            );
+            log.divergence = Some(tick);
+        }
+    }
+}
+
+fn write_checksum_log(log: Res<ChecksumLog>) {
+    if log.mode != ChecksumMode::Record {
+        return;
+    }
+    let contents: String = log
+        .checksums
+        .iter()
+        .enumerate()
+        .map(|(tick, checksum)| format!("{tick} {checksum:016x}\n"))
+        .collect();
+    match fs::write(&log.path, contents) {
+        Ok(()) => info!(
+            "Wrote {} checksums to {}",
+            log.checksums.len(),
+            log.path.display()
+        ),
+        Err(e) => error!("Failed to write checksums to {}: {e}", log.path.display()),
+    }
+}
+
+fn digest(f: impl FnOnce(&mut Fnv1a)) -> u64 {
+    let mut hasher = Fnv1a::default();
+    f(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_transform(tf: &Transform, h: &mut impl Hasher) {
+    for value in tf
+        .translation
+        .to_array()
+        .into_iter()
+        .chain(tf.rotation.to_array())
+        .chain(tf.scale.to_array())
+    {
+        h.write_u32(value.to_bits());
+    }
+}
+
+fn hash_timer(timer: &Timer, h: &mut impl Hasher) {
+    hash_duration(timer.elapsed(), h);
+    hash_duration(timer.duration(), h);
+}
+
+fn hash_duration(duration: Duration, h: &mut impl Hasher) {
+    h.write_u128(duration.as_nanos());
+}
+
+/// A 64-bit FNV-1a hasher. Unlike [`std::hash::DefaultHasher`], its output is stable across
+/// Rust versions and platforms, so checksum logs can be compared between builds.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+}
@@ -1,25 +1,63 @@
 use crate::{
-    audio::sound_effect, menus::credits::CreditsAssets, screens::Screen,
-    theme::interaction::InteractionAssets,
+    asset_tracking::{AppAssetGroupExt, AssetCollection, FallbackResource, ResourceHandles},
+    audio::{AppMusicExt, AppMusicFilterExt, MusicCollection, MusicFilter, Playlist},
+    game::{
+        level_config::{LevelCompleted, LevelConfig, LevelProgress},
+        replay,
+    },
+    save::SaveProfile,
+    screens::Screen,
 };
 use bevy::{input::mouse::MouseButtonInput, prelude::*};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_pancam::PanCam;
+use bevy_seedling::sample::AudioSample;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins(LdtkPlugin)
         .insert_resource(LevelSelection::index(0))
+        .init_resource::<LevelProject>()
+        .add_message::<FishFed>()
+        .add_message::<FoodDropped>()
+        .add_message::<FoodWasted>()
+        // Loaded on the loading screen, and kept for the error screen to list its failures.
+        .add_asset_group(
+            "gameplay",
+            [Screen::Loading, Screen::LoadError, Screen::Gameplay],
+        )
+        .add_music::<LevelAssets>(Screen::Gameplay)
+        // The tank is heard from under water.
+        .add_music_filter(Screen::Gameplay, MusicFilter::UNDERWATER)
         .register_ldtk_entity::<FishGrey>("Fish_grey")
         .register_ldtk_entity::<FishGold>("Fish_golden")
         .register_ldtk_entity::<Alga1x1Bundle>("Alga_1x1")
         .register_ldtk_entity::<Alga1x2Bundle>("Alga_1x2")
         .register_ldtk_entity::<Player>("Player")
-        .add_systems(OnEnter(Screen::Gameplay), setup)
+        .add_systems(
+            PreUpdate,
+            track_level_project.run_if(resource_changed::<LevelProject>),
+        )
+        .add_systems(
+            OnEnter(Screen::Gameplay),
+            (spawn_level, enable_camera_panning),
+        )
+        .add_systems(
+            Update,
+            preload_next_level
+                .run_if(in_state(Screen::Gameplay).and(resource_changed::<LevelConfig>)),
+        )
+        // After the completion is saved in `FixedPostUpdate`, with the level it was for. Tools
+        // without a profile don't play the campaign, they stay on their level.
+        .add_systems(
+            Update,
+            advance_level.run_if(in_state(Screen::Gameplay).and(resource_exists::<SaveProfile>)),
+        )
         .add_systems(
             Update,
             (
                 on_player_spawn,
-                player_control,
+                // A replay moves the player instead.
+                player_control.run_if(not(replay::is_playing)),
                 on_fish_spawn,
                 on_alga1_spawn,
                 on_alga2_spawn,
@@ -32,31 +70,150 @@ pub(super) fn plugin(app: &mut App) {
         );
 }
 
-fn setup(
-    mut commands: Commands,
+/// The campaign's LDtk projects, in the order they are unlocked.
+pub const LEVELS: &[&str] = &["levels/level_1.ldtk"];
+
+/// The identifiers of the LDtk entities registered above. Any other entity in a level is spawned
+/// without its game logic, which `dreamadness-check-levels` reports.
+pub const ENTITIES: &[&str] = &["Fish_grey", "Fish_golden", "Alga_1x1", "Alga_1x2", "Player"];
+
+/// The level of the campaign that follows `level`, if any.
+pub fn next_level(level: &str) -> Option<&'static str> {
+    LEVELS
+        .iter()
+        .position(|l| *l == level)
+        .and_then(|i| LEVELS.get(i + 1))
+        .copied()
+}
+
+/// The LDtk project spawned when entering [`Screen::Gameplay`].
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct LevelProject(pub String);
+
+impl Default for LevelProject {
+    fn default() -> Self {
+        Self(LEVELS[0].to_string())
+    }
+}
+
+/// A fish caught a food ball.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct FishFed {
+    pub fish: Entity,
+}
+
+/// The player dropped a food ball.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct FoodDropped {
+    pub ball: Entity,
+}
+
+/// A food ball sank to the bottom of the tank without being eaten.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct FoodWasted {
+    /// Where the ball was when it reached the bottom.
+    pub position: Vec3,
+}
+
+/// Starts loading the level as soon as it's picked, so the loading screen waits for it. The
+/// previous level is released. A level preloaded by [`preload_next_level`] is already loaded.
+fn track_level_project(
     asset_server: Res<AssetServer>,
-    mut cam: Single<&mut PanCam>,
-    music: Res<CreditsAssets>,
+    project: Res<LevelProject>,
+    mut resource_handles: ResMut<ResourceHandles>,
 ) {
+    let handle = asset_server.load::<LdtkProject>(&project.0);
+    resource_handles.release_group("level");
+    resource_handles.track(handle.untyped(), Some("level"));
+}
+
+/// Starts loading the next level while this one is played, so moving on to it doesn't wait for
+/// its project and tilesets. The level's config can pick another one than the campaign's. A
+/// level that fails to preload only fails once it's moved on to.
+fn preload_next_level(
+    asset_server: Res<AssetServer>,
+    project: Res<LevelProject>,
+    config: Res<LevelConfig>,
+    mut resource_handles: ResMut<ResourceHandles>,
+) {
+    // Load before releasing, so a level that is still next isn't unloaded in between.
+    let handle = config
+        .next_level(&project.0)
+        .map(|next| asset_server.load::<LdtkProject>(next.to_string()));
+    resource_handles.release_group("next_level");
+    if let Some(handle) = handle {
+        resource_handles.preload(handle.untyped(), "next_level");
+    }
+}
+
+/// Moves on to the next level once this one is completed. It was preloaded, so the loading
+/// screen goes straight through.
+fn advance_level(
+    mut completed: MessageReader<LevelCompleted>,
+    config: Res<LevelConfig>,
+    mut project: ResMut<LevelProject>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if completed.read().count() == 0 {
+        return;
+    }
+    if let Some(next) = config.next_level(&project.0) {
+        info!("Moving on to {next}");
+        project.0 = next.to_string();
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn spawn_level(mut commands: Commands, asset_server: Res<AssetServer>, project: Res<LevelProject>) {
     commands.spawn((
         LdtkWorldBundle {
-            ldtk_handle: asset_server.load("levels/level_1.ldtk").into(),
+            ldtk_handle: asset_server.load(&project.0).into(),
             transform: Transform::from_translation(Vec3::new(-192., -216., 0.0)),
             ..Default::default()
         },
         Name::new("Level"),
         DespawnOnExit(Screen::Gameplay),
     ));
+}
+
+fn enable_camera_panning(mut cam: Single<&mut PanCam>) {
     cam.enabled = true;
-    commands.spawn((
-        Name::new("Fish Level Music"),
-        DespawnOnExit(Screen::Gameplay),
-        crate::audio::music(music.music.clone()),
-    ));
 }
 
-#[derive(Component)]
-struct AlgaTimer(Timer);
+/// Assets of the fish level, loaded with the `gameplay` group before entering
+/// [`Screen::Gameplay`].
+#[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
+#[asset_collection(fallback)]
+#[reflect(Resource)]
+pub struct LevelAssets {
+    #[dependency]
+    #[asset(group = "gameplay")]
+    pub music: Handle<AudioSample>,
+}
+
+impl FallbackResource for LevelAssets {
+    /// The level can be played in silence.
+    fn fallback(_: &mut World) -> Self {
+        Self {
+            music: Handle::default(),
+        }
+    }
+}
+
+impl MusicCollection for LevelAssets {
+    fn playlist(&self) -> Playlist {
+        // The fallback has no music.
+        if self.music == Handle::default() {
+            return Playlist::default();
+        }
+        Playlist::single(self.music.clone())
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct AlgaTimer(pub(crate) Timer);
 
 impl Default for AlgaTimer {
     fn default() -> Self {
@@ -64,6 +221,21 @@ impl Default for AlgaTimer {
     }
 }
 
+impl From<&EntityInstance> for AlgaTimer {
+    fn from(entity: &EntityInstance) -> Self {
+        let frame_duration = float_field(entity, "Frame_duration", 0.5);
+        Self(Timer::from_seconds(frame_duration, TimerMode::Once))
+    }
+}
+
+/// The float field `identifier` of an LDtk entity, or `default` if it has none.
+fn float_field(entity: &EntityInstance, identifier: &str, default: f32) -> f32 {
+    entity
+        .get_float_field(identifier)
+        .copied()
+        .unwrap_or(default)
+}
+
 #[derive(Default, Component)]
 struct Alga1x1;
 
@@ -72,6 +244,7 @@ pub struct Alga1x1Bundle {
     #[sprite]
     sprite: Sprite,
     alga: Alga1x1,
+    #[from_entity_instance]
     marler: AlgaTimer,
 }
 
@@ -83,11 +256,13 @@ pub struct Alga1x2Bundle {
     #[sprite]
     sprite: Sprite,
     alga: Alga1x2,
+    #[from_entity_instance]
     marler: AlgaTimer,
 }
 
-#[derive(Default, Component, PartialEq)]
-enum FishState {
+#[derive(Default, Component, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub enum FishState {
     #[default]
     Idle,
     SlowLeft,
@@ -98,8 +273,9 @@ enum FishState {
     EatingRight,
 }
 
-#[derive(Component)]
-struct FishTimer(Timer);
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct FishTimer(pub(crate) Timer);
 
 impl Default for FishTimer {
     fn default() -> Self {
@@ -107,6 +283,91 @@ impl Default for FishTimer {
     }
 }
 
+/// How fast a fish swims, in pixels per fixed tick.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct FishSpeed {
+    pub slow: f32,
+    /// When food is close.
+    pub fast: f32,
+}
+
+impl Default for FishSpeed {
+    fn default() -> Self {
+        Self {
+            slow: 1.0,
+            fast: 3.0,
+        }
+    }
+}
+
+impl From<&EntityInstance> for FishSpeed {
+    fn from(entity: &EntityInstance) -> Self {
+        let default = Self::default();
+        Self {
+            slow: float_field(entity, "Slow_speed", default.slow),
+            fast: float_field(entity, "Fast_speed", default.fast),
+        }
+    }
+}
+
+/// Where a fish turns around, as x positions in the level.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Patrol {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for Patrol {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 384.0,
+        }
+    }
+}
+
+impl From<&EntityInstance> for Patrol {
+    fn from(entity: &EntityInstance) -> Self {
+        let default = Self::default();
+        Self {
+            min: float_field(entity, "Patrol_min", default.min),
+            max: float_field(entity, "Patrol_max", default.max),
+        }
+    }
+}
+
+/// How long a fish eats when fed, in seconds.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct Hunger(pub f32);
+
+impl Default for Hunger {
+    fn default() -> Self {
+        Self(5.0)
+    }
+}
+
+impl From<&EntityInstance> for Hunger {
+    fn from(entity: &EntityInstance) -> Self {
+        Self(float_field(entity, "Hunger", Self::default().0))
+    }
+}
+
+/// The key the player collects by feeding the fish, stored in the [`SaveProfile`].
+///
+/// [`SaveProfile`]: crate::save::SaveProfile
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component)]
+pub struct KeyDrop(pub Option<String>);
+
+impl From<&EntityInstance> for KeyDrop {
+    fn from(entity: &EntityInstance) -> Self {
+        Self(entity.get_maybe_string_field("Key").ok().cloned().flatten())
+    }
+}
+
 #[derive(Bundle, LdtkEntity)]
 pub struct FishGrey {
     #[sprite]
@@ -115,6 +376,12 @@ pub struct FishGrey {
     despawn: DespawnOnExit<Screen>,
     fish: FishState,
     timer: FishTimer,
+    #[from_entity_instance]
+    speed: FishSpeed,
+    #[from_entity_instance]
+    patrol: Patrol,
+    #[from_entity_instance]
+    hunger: Hunger,
 }
 
 impl Default for FishGrey {
@@ -125,12 +392,15 @@ impl Default for FishGrey {
             despawn: DespawnOnExit(Screen::Gameplay),
             fish: FishState::default(),
             timer: FishTimer::default(),
+            speed: FishSpeed::default(),
+            patrol: Patrol::default(),
+            hunger: Hunger::default(),
         }
     }
 }
 
 #[derive(Default, Component)]
-struct GoldMarker;
+pub struct GoldMarker;
 
 #[derive(Bundle, LdtkEntity)]
 pub struct FishGold {
@@ -141,6 +411,14 @@ pub struct FishGold {
     fish: FishState,
     gold_marker: GoldMarker,
     timer: FishTimer,
+    #[from_entity_instance]
+    speed: FishSpeed,
+    #[from_entity_instance]
+    patrol: Patrol,
+    #[from_entity_instance]
+    hunger: Hunger,
+    #[from_entity_instance]
+    key: KeyDrop,
 }
 
 impl Default for FishGold {
@@ -152,6 +430,10 @@ impl Default for FishGold {
             fish: FishState::default(),
             gold_marker: GoldMarker,
             timer: FishTimer::default(),
+            speed: FishSpeed::default(),
+            patrol: Patrol::default(),
+            hunger: Hunger::default(),
+            key: KeyDrop::default(),
         }
     }
 }
@@ -159,20 +441,24 @@ impl Default for FishGold {
 fn update_fish(
     mut query: Query<
         (
+            Entity,
             &mut FishState,
             &mut Transform,
             &mut FishTimer,
             &GlobalTransform,
             &mut Sprite,
+            &FishSpeed,
+            &Patrol,
+            &Hunger,
         ),
         Without<Ball>,
     >,
     ball_query: Query<(Entity, &GlobalTransform), With<Ball>>,
     mut commands: Commands,
     time: Res<Time>,
-    sounds: If<Res<InteractionAssets>>,
+    mut fed: MessageWriter<FishFed>,
 ) {
-    for (mut state, mut tf, mut timer, gtf, mut sprite) in &mut query {
+    for (fish, mut state, mut tf, mut timer, gtf, mut sprite, speed, patrol, hunger) in &mut query {
         // Update eating timer
         timer.0.tick(time.delta());
 
@@ -207,8 +493,8 @@ fn update_fish(
                 FishState::EatingLeft => FishState::EatingLeft,
                 FishState::EatingRight => FishState::EatingRight,
             };
-            timer.0 = Timer::from_seconds(5.0, TimerMode::Once);
-            commands.spawn(sound_effect(sounds.click.clone()));
+            timer.0 = Timer::from_seconds(hunger.0, TimerMode::Once);
+            fed.write(FishFed { fish });
         } else {
             // Check if eating timer has finished
             if is_eating && timer.0.remaining().as_secs() == 0 {
@@ -228,17 +514,16 @@ fn update_fish(
                 FishState::SlowLeft => {
                     if timer.0.remaining().as_millis() == 0 {
                         timer.0 = Timer::from_seconds(0.5, TimerMode::Once);
-                        sprite.texture_atlas.as_mut().and_then(|t| {
+                        if let Some(t) = sprite.texture_atlas.as_mut() {
                             t.index += 3;
                             if t.index >= 9 {
                                 t.index = 0;
                             }
-                            Some(())
-                        });
+                        }
                         sprite.flip_x = false;
                     }
-                    tf.translation.x -= 1.0;
-                    if tf.translation.x < 0.0 {
+                    tf.translation.x -= speed.slow;
+                    if tf.translation.x < patrol.min {
                         FishState::SlowRight
                     } else {
                         if going_faster {
@@ -251,17 +536,16 @@ fn update_fish(
                 FishState::FastLeft => {
                     if timer.0.remaining().as_millis() == 0 {
                         timer.0 = Timer::from_seconds(0.1, TimerMode::Once);
-                        sprite.texture_atlas.as_mut().and_then(|t| {
+                        if let Some(t) = sprite.texture_atlas.as_mut() {
                             t.index += 3;
                             if t.index >= 9 {
                                 t.index = 0;
                             }
-                            Some(())
-                        });
+                        }
                         sprite.flip_x = false;
                     }
-                    tf.translation.x -= 3.0;
-                    if tf.translation.x < 0.0 {
+                    tf.translation.x -= speed.fast;
+                    if tf.translation.x < patrol.min {
                         FishState::SlowRight
                     } else {
                         if going_faster {
@@ -274,17 +558,16 @@ fn update_fish(
                 FishState::SlowRight => {
                     if timer.0.remaining().as_millis() == 0 {
                         timer.0 = Timer::from_seconds(0.5, TimerMode::Once);
-                        sprite.texture_atlas.as_mut().and_then(|t| {
+                        if let Some(t) = sprite.texture_atlas.as_mut() {
                             t.index += 3;
                             if t.index >= 9 {
                                 t.index = 0;
                             }
-                            Some(())
-                        });
+                        }
                         sprite.flip_x = true
                     }
-                    tf.translation.x += 1.0;
-                    if tf.translation.x > 384.0 {
+                    tf.translation.x += speed.slow;
+                    if tf.translation.x > patrol.max {
                         FishState::SlowLeft
                     } else {
                         if going_faster {
@@ -297,17 +580,16 @@ fn update_fish(
                 FishState::FastRight => {
                     if timer.0.remaining().as_millis() == 0 {
                         timer.0 = Timer::from_seconds(0.1, TimerMode::Once);
-                        sprite.texture_atlas.as_mut().and_then(|t| {
+                        if let Some(t) = sprite.texture_atlas.as_mut() {
                             t.index += 3;
                             if t.index >= 9 {
                                 t.index = 0;
                             }
-                            Some(())
-                        });
+                        }
                         sprite.flip_x = true
                     }
-                    tf.translation.x += 3.0;
-                    if tf.translation.x > 384.0 {
+                    tf.translation.x += speed.fast;
+                    if tf.translation.x > patrol.max {
                         FishState::SlowLeft
                     } else {
                         if going_faster {
@@ -324,11 +606,13 @@ fn update_fish(
     }
 }
 
-#[derive(Default, Component)]
-struct PlayerMarker;
+#[derive(Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerMarker;
 
-#[derive(Component)]
-struct BallSpawnTimer(Timer);
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct BallSpawnTimer(pub Timer);
 
 impl Default for BallSpawnTimer {
     fn default() -> Self {
@@ -346,34 +630,50 @@ pub struct Player {
     spawn_timer: BallSpawnTimer,
 }
 
+/// The image of the player, loaded with the `gameplay` group.
+#[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
+#[reflect(Resource)]
+pub struct PlayerAssets {
+    #[dependency]
+    #[asset(group = "gameplay")]
+    pub player: Handle<Image>,
+}
+
 fn on_player_spawn(
     mut player: Single<(&mut Sprite, &mut Name), Added<PlayerMarker>>,
-    server: Res<AssetServer>,
+    assets: If<Res<PlayerAssets>>,
 ) {
-    player.0.image = server.load("images/player.png");
+    player.0.image = assets.player.clone();
     player.0.custom_size = Some(Vec2::splat(32.));
     *player.1 = Name::new("Player");
 }
 
-#[derive(Default, Component)]
-struct Ball;
+#[derive(Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Ball;
 
 fn ball(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     tf: Transform,
 ) -> impl Bundle {
+    (ball_visuals(&mut meshes, &mut materials), tf, Ball)
+}
+
+/// Everything a [`Ball`] needs besides its position, e.g. when restoring a quicksave.
+pub(crate) fn ball_visuals(
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) -> impl Bundle + use<> {
     (
         Mesh2d(meshes.add(Circle::new(4.))),
         MeshMaterial2d(materials.add(ColorMaterial::from(Color::BLACK))),
-        tf,
-        Ball,
         Name::new("Ball"),
         DespawnOnExit(Screen::Gameplay),
     )
 }
 
-fn player_control(
+pub(crate) fn player_control(
     camera: Single<(&Camera, &GlobalTransform), Without<PlayerMarker>>,
     mut commands: Commands,
     meshes: ResMut<Assets<Mesh>>,
@@ -385,7 +685,8 @@ fn player_control(
     mut mouse_button_input_reader: MessageReader<MouseButtonInput>,
     mut cursor_moved_reader: MessageReader<CursorMoved>,
     time: Res<Time>,
-    sounds: If<Res<InteractionAssets>>,
+    mut dropped: MessageWriter<FoodDropped>,
+    mut progress: ResMut<LevelProgress>,
 ) {
     let (mut player_tf, player_global_tf, mut spawn_timer) = player_query.into_inner();
 
@@ -393,11 +694,14 @@ fn player_control(
     spawn_timer.0.tick(time.delta());
 
     for mouse_button_input in mouse_button_input_reader.read() {
-        if mouse_button_input.state.is_pressed() && spawn_timer.0.remaining_secs() == 0.0 {
+        if mouse_button_input.state.is_pressed()
+            && spawn_timer.0.remaining_secs() == 0.0
+            && progress.take_food()
+        {
             let mut tf = Transform::from_translation(player_global_tf.translation());
             tf.translation.z = 10.0;
-            commands.spawn(ball(meshes, materials, tf));
-            commands.spawn(sound_effect(sounds.hover.clone()));
+            let ball = commands.spawn(ball(meshes, materials, tf)).id();
+            dropped.write(FoodDropped { ball });
             spawn_timer.0.reset();
             break;
         }
@@ -418,11 +722,18 @@ fn player_control(
     }
 }
 
-fn update_ball(mut commands: Commands, mut query: Query<(Entity, &mut Transform), With<Ball>>) {
+fn update_ball(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), With<Ball>>,
+    mut wasted: MessageWriter<FoodWasted>,
+) {
     for (entity, mut tf) in &mut query {
         tf.translation.y -= 1.0;
         if tf.translation.y < -216.0 {
             commands.entity(entity).try_despawn();
+            wasted.write(FoodWasted {
+                position: tf.translation,
+            });
         }
     }
 }
@@ -483,10 +794,38 @@ fn alga_update(mut alga: Query<(&mut Sprite, &mut AlgaTimer)>, time: Res<Time>)
         algatimer.0.tick(time.delta());
         if algatimer.0.remaining().as_millis() == 0 {
             algatimer.0.reset();
-            sprite.texture_atlas.as_mut().and_then(|t| {
+            if let Some(t) = sprite.texture_atlas.as_mut() {
                 t.index = (t.index + 1) % 2;
-                Some(())
-            });
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs_ldtk::app::LdtkEntityMap;
+
+    use super::*;
+    use crate::AppPlugin;
+
+    #[test]
+    fn entities_match_the_registered_ones() {
+        let mut app = App::new();
+        app.add_plugins(
+            AppPlugin::new()
+                .headless(true)
+                .editor(false)
+                .dev_tools(false),
+        );
+        let mut registered: Vec<&str> = app
+            .world()
+            .non_send_resource::<LdtkEntityMap>()
+            .keys()
+            .filter_map(|(_, identifier)| identifier.as_deref())
+            .collect();
+        registered.sort_unstable();
+        let mut entities = ENTITIES.to_vec();
+        entities.sort_unstable();
+        assert_eq!(registered, entities);
+    }
+}
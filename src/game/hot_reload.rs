@@ -0,0 +1,163 @@
+//! Hot reloading of the level while it's played, for designers iterating in LDtk.
+//!
+//! When the LDtk project changes on disk, `bevy_ecs_ldtk` despawns the level and spawns it
+//! again. On top of that, the food balls are despawned, the player is put back where it was,
+//! and systems holding on to level entities are told about their respawned counterparts with
+//! [`LevelReloaded`], so fish that were fed stay fed. The camera and the score are left alone.
+//! What changed in the level is logged.
+
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::{ecs::entity::EntityHashMap, prelude::*};
+use bevy_ecs_ldtk::{assets::LdtkProject, prelude::*};
+
+use crate::{
+    game::fish_level::{Ball, LevelProject, PlayerMarker},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_message::<LevelReloaded>();
+    app.init_resource::<LevelLayout>();
+    app.add_systems(OnExit(Screen::Gameplay), cancel_reload);
+    app.add_systems(
+        Update,
+        (start_reload, finish_reload)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The level was reloaded, and its entities respawned.
+#[derive(Message, Debug, Clone)]
+pub struct LevelReloaded {
+    /// The respawned counterpart of each entity that is still in the level, matched by their
+    /// [`EntityIid`].
+    pub entities: EntityHashMap<Entity>,
+}
+
+/// Where each entity of the level is in the LDtk project, by [`EntityIid`], to log what changed
+/// on reload.
+#[derive(Resource, Debug, Default)]
+struct LevelLayout(BTreeMap<String, (String, IVec2)>);
+
+impl LevelLayout {
+    fn from_project(project: &LdtkProject) -> Self {
+        let entities = project
+            .json_data()
+            .levels
+            .iter()
+            .flat_map(|level| level.layer_instances.iter().flatten())
+            .flat_map(|layer| &layer.entity_instances)
+            .map(|entity| (entity.iid.clone(), (entity.identifier.clone(), entity.px)))
+            .collect();
+        Self(entities)
+    }
+}
+
+/// A reload waiting for the level to spawn again.
+#[derive(Resource, Debug)]
+struct PendingReload {
+    /// The entities of the level before the reload.
+    entities: HashMap<EntityIid, Entity>,
+    player_x: Option<f32>,
+}
+
+fn cancel_reload(mut commands: Commands) {
+    commands.remove_resource::<PendingReload>();
+}
+
+fn start_reload(
+    mut commands: Commands,
+    mut project_events: MessageReader<AssetEvent<LdtkProject>>,
+    level: Query<&LdtkProjectHandle>,
+    entities: Query<(Entity, &EntityIid)>,
+    player: Query<&Transform, With<PlayerMarker>>,
+    balls: Query<Entity, With<Ball>>,
+) {
+    let modified: Vec<_> = project_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if !level.iter().any(|handle| modified.contains(&handle.id())) {
+        return;
+    }
+
+    // The food was dropped in a tank that may not be there anymore.
+    for ball in &balls {
+        commands.entity(ball).despawn();
+    }
+    commands.insert_resource(PendingReload {
+        entities: entities
+            .iter()
+            .map(|(entity, iid)| (iid.clone(), entity))
+            .collect(),
+        player_x: player.single().ok().map(|tf| tf.translation.x),
+    });
+}
+
+fn finish_reload(
+    mut commands: Commands,
+    mut level_events: MessageReader<LevelEvent>,
+    pending: Option<Res<PendingReload>>,
+    entities: Query<(Entity, &EntityIid)>,
+    mut player: Query<&mut Transform, Added<PlayerMarker>>,
+    level: Query<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    project: Res<LevelProject>,
+    mut layout: ResMut<LevelLayout>,
+    mut reloaded: MessageWriter<LevelReloaded>,
+) {
+    if !level_events
+        .read()
+        .any(|event| matches!(event, LevelEvent::Spawned(_)))
+    {
+        return;
+    }
+    // The layout is recorded whenever the level spawns, to compare it with the next reload.
+    let new_layout = level
+        .iter()
+        .find_map(|handle| projects.get(handle.id()))
+        .map(LevelLayout::from_project)
+        .unwrap_or_default();
+    let old_layout = std::mem::replace(&mut *layout, new_layout);
+    let Some(pending) = pending else {
+        return;
+    };
+    commands.remove_resource::<PendingReload>();
+
+    let entities = entities
+        .iter()
+        .filter_map(|(entity, iid)| Some((*pending.entities.get(iid)?, entity)))
+        .collect();
+    reloaded.write(LevelReloaded { entities });
+
+    // Otherwise the player would be back where the designer placed it until the cursor moves.
+    if let (Some(x), Ok(mut tf)) = (pending.player_x, player.single_mut()) {
+        tf.translation.x = x;
+    }
+
+    log_changes(&project.0, &old_layout, &layout);
+}
+
+fn log_changes(project: &str, old: &LevelLayout, new: &LevelLayout) {
+    let mut changes = 0;
+    for (iid, (identifier, px)) in &new.0 {
+        match old.0.get(iid) {
+            None => info!("Added {identifier} at {px}"),
+            Some((_, old_px)) if old_px != px => info!("Moved {identifier} from {old_px} to {px}"),
+            Some(_) => continue,
+        }
+        changes += 1;
+    }
+    for (iid, (identifier, px)) in &old.0 {
+        if !new.0.contains_key(iid) {
+            info!("Removed {identifier} at {px}");
+            changes += 1;
+        }
+    }
+    info!("Reloaded {project}, {changes} entities changed");
+}
@@ -0,0 +1,35 @@
+//! Drives the [`MusicIntensity`] from the gameplay: the dream grows more chaotic as the fish
+//! wake up and get fed.
+
+use bevy::prelude::*;
+
+use crate::{
+    audio::MusicIntensity,
+    game::{fish_level::FishState, level_config::LevelProgress},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), reset_intensity);
+    app.add_systems(OnExit(Screen::Gameplay), reset_intensity);
+    app.add_systems(Update, update_intensity.run_if(in_state(Screen::Gameplay)));
+}
+
+fn reset_intensity(mut intensity: ResMut<MusicIntensity>) {
+    intensity.0 = 0.0;
+}
+
+/// The intensity is the share of the fish that have been fed.
+fn update_intensity(
+    progress: Res<LevelProgress>,
+    mut intensity: ResMut<MusicIntensity>,
+    fish: Query<(), With<FishState>>,
+) {
+    let total = fish.iter().count();
+    let share = if total == 0 {
+        0.0
+    } else {
+        progress.fed_fish().len() as f32 / total as f32
+    };
+    intensity.set_if_neq(MusicIntensity(share));
+}
@@ -0,0 +1,288 @@
+//! Level-wide settings, read from the custom fields of the LDtk level when it spawns.
+//!
+//! Every field can be left empty in LDtk: such a level is completed by feeding every fish, has
+//! no time limit and unlimited food, plays the gameplay music and unlocks the next level of the
+//! campaign.
+//!
+//! The [`LevelProgress`] towards the objective is tracked here too, and [`LevelCompleted`] is
+//! sent once it's met. A level that runs out of time is restarted.
+
+use std::{
+    hash::{Hash, Hasher},
+    time::Duration,
+};
+
+use bevy::{
+    ecs::{entity::MapEntities, reflect::ReflectMapEntities},
+    platform::collections::HashSet,
+    prelude::*,
+};
+use bevy_ecs_ldtk::{
+    assets::LdtkProject,
+    ldtk::{Level, ldtk_fields::LdtkFieldsError},
+    prelude::*,
+};
+use bevy_seedling::sample::AudioSample;
+
+use crate::{
+    PausableSystems,
+    audio::{AppMusicExt, MusicCollection, Playlist},
+    game::{
+        fish_level::{FishFed, FishState, GoldMarker, LevelProject, next_level},
+        hot_reload::LevelReloaded,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelConfig>();
+    app.init_resource::<LevelProgress>();
+    app.add_message::<LevelCompleted>();
+    app.add_music::<LevelMusic>(Screen::Gameplay);
+    app.add_systems(OnEnter(Screen::Gameplay), reset_level);
+    app.add_systems(OnExit(Screen::Gameplay), remove_level_music);
+    app.add_systems(
+        Update,
+        (read_level_config, remap_fed_fish).run_if(in_state(Screen::Gameplay)),
+    );
+    app.add_systems(
+        FixedPostUpdate,
+        (tick_progress.in_set(PausableSystems), track_progress)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// The settings of the current level.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct LevelConfig {
+    pub objective: Objective,
+    /// The level is restarted if the objective isn't met in time.
+    pub time_limit: Option<Duration>,
+    /// Path of the music to play instead of the gameplay music, relative to `assets/`.
+    pub music: Option<String>,
+    /// How many food balls the player can drop, unlimited if `None`.
+    pub starting_food: Option<u32>,
+    /// The LDtk project unlocked by completing the level, instead of the next one of the
+    /// campaign.
+    pub next_level: Option<String>,
+}
+
+/// What the player has to do to complete a level.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum Objective {
+    /// Feed every fish at least once.
+    #[default]
+    FeedAll,
+    /// Feed the fish this many times in total.
+    Feedings(u32),
+    /// Feed a golden fish.
+    FeedGolden,
+}
+
+impl LevelConfig {
+    fn from_level(level: &Level) -> Self {
+        let target = field(level.get_maybe_int_field("Objective_target"));
+        let objective = match field(level.get_maybe_enum_field("Objective")).as_deref() {
+            None | Some("Feed_all") => Objective::FeedAll,
+            Some("Feed_golden") => Objective::FeedGolden,
+            Some("Feedings") => match target.and_then(|target| u32::try_from(target).ok()) {
+                Some(target) => Objective::Feedings(target),
+                None => {
+                    warn!("The Feedings objective needs an Objective_target, feeding all fish");
+                    Objective::FeedAll
+                }
+            },
+            Some(other) => {
+                warn!("Unknown objective {other}, feeding all fish");
+                Objective::FeedAll
+            }
+        };
+        Self {
+            objective,
+            time_limit: field(level.get_maybe_float_field("Time_limit"))
+                .and_then(|secs| Duration::try_from_secs_f32(secs).ok()),
+            music: field(level.get_maybe_string_field("Music")),
+            starting_food: field(level.get_maybe_int_field("Starting_food"))
+                .map(|food| food.max(0) as u32),
+            next_level: field(level.get_maybe_string_field("Next_level")),
+        }
+    }
+
+    /// The level unlocked by completing `level`, if any.
+    pub fn next_level<'a>(&'a self, level: &str) -> Option<&'a str> {
+        self.next_level.as_deref().or_else(|| next_level(level))
+    }
+}
+
+/// The value of an optional LDtk field, `None` if it's empty or missing.
+fn field<T: Clone>(value: Result<&Option<T>, LdtkFieldsError>) -> Option<T> {
+    value.ok().cloned().flatten()
+}
+
+/// How far the player is in the current level.
+#[derive(Resource, Debug, Default, Reflect, MapEntities)]
+#[reflect(Resource, MapEntities)]
+pub struct LevelProgress {
+    /// The fish fed at least once.
+    #[entities]
+    fed_fish: HashSet<Entity>,
+    feedings: u32,
+    ticks: u32,
+    food_left: Option<u32>,
+    completed: bool,
+    /// Whether the level has spawned since it was entered, it's only hot reloaded after that.
+    spawned: bool,
+}
+
+impl LevelProgress {
+    /// Hashes the progress for a checksum. Entity ids differ between runs, so only how many
+    /// fish were fed is hashed.
+    pub(crate) fn hash_progress(&self, h: &mut impl Hasher) {
+        self.fed_fish.len().hash(h);
+        self.feedings.hash(h);
+        self.ticks.hash(h);
+        self.food_left.hash(h);
+        self.completed.hash(h);
+    }
+
+    /// The fish fed at least once.
+    pub fn fed_fish(&self) -> &HashSet<Entity> {
+        &self.fed_fish
+    }
+
+    /// Takes a food ball from the player's supply, if there is any left.
+    pub fn take_food(&mut self) -> bool {
+        match &mut self.food_left {
+            None => true,
+            Some(0) => false,
+            Some(food) => {
+                *food -= 1;
+                true
+            }
+        }
+    }
+}
+
+/// The objective of the level was met.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct LevelCompleted {
+    pub time_secs: f32,
+}
+
+/// The music of a level that picks its own in LDtk.
+#[derive(Resource, Debug)]
+struct LevelMusic(Handle<AudioSample>);
+
+impl MusicCollection for LevelMusic {
+    fn playlist(&self) -> Playlist {
+        Playlist::single(self.0.clone())
+    }
+}
+
+fn reset_level(mut config: ResMut<LevelConfig>, mut progress: ResMut<LevelProgress>) {
+    *config = LevelConfig::default();
+    *progress = LevelProgress::default();
+}
+
+fn remove_level_music(mut commands: Commands) {
+    commands.remove_resource::<LevelMusic>();
+}
+
+/// Reads the config whenever the level spawns, hot reloads included. The player keeps the food
+/// they have left through a hot reload.
+fn read_level_config(
+    mut commands: Commands,
+    mut level_events: MessageReader<LevelEvent>,
+    level: Query<&LdtkProjectHandle>,
+    projects: Res<Assets<LdtkProject>>,
+    asset_server: Res<AssetServer>,
+    mut config: ResMut<LevelConfig>,
+    mut progress: ResMut<LevelProgress>,
+) {
+    for event in level_events.read() {
+        let LevelEvent::Spawned(iid) = event else {
+            continue;
+        };
+        let Some(raw_level) = level
+            .iter()
+            .filter_map(|handle| projects.get(handle.id()))
+            .flat_map(|project| &project.json_data().levels)
+            .find(|raw_level| raw_level.iid == *iid.get())
+        else {
+            continue;
+        };
+        let new_config = LevelConfig::from_level(raw_level);
+        debug!("Level config: {new_config:?}");
+        if !progress.spawned {
+            progress.food_left = new_config.starting_food;
+            progress.spawned = true;
+        }
+        match &new_config.music {
+            Some(music) => commands.insert_resource(LevelMusic(asset_server.load(music))),
+            None => commands.remove_resource::<LevelMusic>(),
+        }
+        config.set_if_neq(new_config);
+    }
+}
+
+/// Fish respawned by a hot reload of the level are still fed.
+fn remap_fed_fish(mut reloaded: MessageReader<LevelReloaded>, mut progress: ResMut<LevelProgress>) {
+    for LevelReloaded { entities } in reloaded.read() {
+        progress.fed_fish = progress
+            .fed_fish
+            .iter()
+            .filter_map(|fish| entities.get(fish).copied())
+            .collect();
+    }
+}
+
+/// The time limit doesn't run while the game is paused.
+fn tick_progress(mut progress: ResMut<LevelProgress>) {
+    progress.ticks += 1;
+}
+
+fn track_progress(
+    mut progress: ResMut<LevelProgress>,
+    config: Res<LevelConfig>,
+    project: Res<LevelProject>,
+    mut fed: MessageReader<FishFed>,
+    fish: Query<Has<GoldMarker>, With<FishState>>,
+    time: Res<Time<Fixed>>,
+    mut completed: MessageWriter<LevelCompleted>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    for FishFed { fish } in fed.read() {
+        progress.fed_fish.insert(*fish);
+        progress.feedings += 1;
+    }
+    if progress.completed {
+        return;
+    }
+
+    let objective_met = match config.objective {
+        Objective::FeedAll => {
+            let fish_total = fish.iter().count();
+            fish_total > 0 && progress.fed_fish.len() >= fish_total
+        }
+        Objective::Feedings(target) => progress.feedings >= target,
+        Objective::FeedGolden => progress
+            .fed_fish
+            .iter()
+            .any(|entity| fish.get(*entity).unwrap_or(false)),
+    };
+    let elapsed = time.timestep() * progress.ticks;
+    if objective_met {
+        progress.completed = true;
+        completed.write(LevelCompleted {
+            time_secs: progress.ticks as f32 * time.timestep().as_secs_f32(),
+        });
+    } else if let Some(limit) = config.time_limit
+        && elapsed >= limit
+    {
+        info!("Ran out of time in {}, restarting", project.0);
+        // The loading screen comes straight back with a fresh level.
+        next_screen.set(Screen::Loading);
+    }
+}
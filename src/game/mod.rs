@@ -1,7 +1,43 @@
 use bevy::prelude::*;
+use rand::{SeedableRng, rngs::StdRng};
 
-pub(crate) mod fish_level;
+pub mod checksum;
+pub mod fish_level;
+pub mod hot_reload;
+pub mod intensity;
+pub mod level_config;
+pub mod quicksave;
+pub mod replay;
+pub mod sounds;
 
 pub(super) fn plugin(app: &mut App) {
-    app.add_plugins(fish_level::plugin);
+    app.init_resource::<GameRng>();
+    app.add_plugins((
+        checksum::plugin,
+        fish_level::plugin,
+        hot_reload::plugin,
+        intensity::plugin,
+        level_config::plugin,
+        quicksave::plugin,
+        replay::plugin,
+        sounds::plugin,
+    ));
+}
+
+/// The source of gameplay randomness, which the gameplay has none of yet. Whatever random
+/// gameplay is added should draw from it, so that seeded runs stay reproducible.
+#[derive(Resource, Debug, Clone)]
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        info!("Gameplay seed: {seed}");
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::from_seed(rand::random())
+    }
 }
@@ -0,0 +1,225 @@
+//! Mid-level quicksaves, press F5 to save and F9 to load.
+//!
+//! A quicksave is a [`DynamicScene`] of the gameplay entities, saved to disk. Loading it
+//! restarts the level through the loading screen and, once the LDtk entities have spawned
+//! again, writes the saved components over them. Entities are matched by their LDtk
+//! [`EntityIid`], and entities that don't come from LDtk (food balls) are spawned anew.
+
+use std::{any::TypeId, fs, io, path::PathBuf};
+
+use bevy::{
+    ecs::entity::EntityHashMap, input::common_conditions::input_just_pressed, prelude::*,
+    scene::serde::SceneDeserializer,
+};
+use bevy_ecs_ldtk::EntityIid;
+use serde::de::DeserializeSeed;
+
+use crate::{
+    game::{
+        fish_level::{
+            AlgaTimer, Ball, BallSpawnTimer, FishState, FishTimer, LevelProject, PlayerMarker,
+            ball_visuals,
+        },
+        level_config::{LevelConfig, LevelProgress},
+    },
+    menus::Menu,
+    save::LevelAttempt,
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            quicksave.run_if(input_just_pressed(KeyCode::F5)),
+            quickload.run_if(input_just_pressed(KeyCode::F9)),
+        )
+            .run_if(in_state(Screen::Gameplay).and(in_state(Menu::None))),
+    );
+    app.add_systems(
+        Update,
+        restore_quicksave
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<PendingQuickload>)),
+    );
+}
+
+/// A quicksave waiting for its level to spawn before being restored.
+#[derive(Resource)]
+struct PendingQuickload(DynamicScene);
+
+/// The quicksave file, in the platform's data directory. There is none on the web.
+fn quicksave_path() -> Option<PathBuf> {
+    Some(
+        dirs::data_dir()?
+            .join("dreamadness")
+            .join("quicksave.scn.ron"),
+    )
+}
+
+fn quicksave(world: &mut World) {
+    let mut entities = world.query_filtered::<Entity, Or<(
+        With<FishState>,
+        With<Ball>,
+        With<PlayerMarker>,
+        With<AlgaTimer>,
+    )>>();
+    let scene = DynamicSceneBuilder::from_world(world)
+        .deny_all()
+        .allow_component::<EntityIid>()
+        .allow_component::<Transform>()
+        .allow_component::<FishState>()
+        .allow_component::<FishTimer>()
+        .allow_component::<AlgaTimer>()
+        .allow_component::<Ball>()
+        .allow_component::<PlayerMarker>()
+        .allow_component::<BallSpawnTimer>()
+        .allow_resource::<LevelProject>()
+        .allow_resource::<LevelAttempt>()
+        .allow_resource::<LevelConfig>()
+        .allow_resource::<LevelProgress>()
+        .extract_entities(entities.iter(world))
+        .extract_resources()
+        .build();
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let result = scene
+        .serialize(&registry)
+        .map_err(io::Error::other)
+        .and_then(write_quicksave);
+    match result {
+        Ok(()) => info!("Quicksaved {} entities", scene.entities.len()),
+        Err(e) => error!("Failed to quicksave: {e}"),
+    }
+}
+
+fn write_quicksave(contents: String) -> io::Result<()> {
+    let Some(path) = quicksave_path() else {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "no data directory on this platform",
+        ));
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)
+}
+
+fn read_quicksave(registry: &AppTypeRegistry) -> io::Result<DynamicScene> {
+    let Some(path) = quicksave_path() else {
+        return Err(io::ErrorKind::NotFound.into());
+    };
+    let contents = fs::read_to_string(path)?;
+    let mut deserializer = ron::Deserializer::from_str(&contents)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    SceneDeserializer {
+        type_registry: &registry.read(),
+    }
+    .deserialize(&mut deserializer)
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn quickload(
+    mut commands: Commands,
+    registry: Res<AppTypeRegistry>,
+    mut project: ResMut<LevelProject>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let scene = match read_quicksave(&registry) {
+        Ok(scene) => scene,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            info!("There is no quicksave to load");
+            return;
+        }
+        Err(e) => {
+            error!("Failed to read the quicksave: {e}");
+            return;
+        }
+    };
+
+    // The quicksave may come from another level, load that one.
+    if let Some(saved) = scene
+        .resources
+        .iter()
+        .find_map(|resource| LevelProject::from_reflect(resource.as_partial_reflect()))
+    {
+        *project = saved;
+    }
+    commands.insert_resource(PendingQuickload(scene));
+    // Leaving gameplay despawns the current level, and the loading screen comes straight back
+    // with a fresh one.
+    next_screen.set(Screen::Loading);
+}
+
+fn restore_quicksave(world: &mut World) {
+    let mut level_entities = world.query::<(Entity, &EntityIid)>();
+    let live: Vec<(Entity, EntityIid)> = level_entities
+        .iter(world)
+        .map(|(entity, iid)| (entity, iid.clone()))
+        .collect();
+    // The LDtk entities all spawn at once, wait for them.
+    if live.is_empty() {
+        return;
+    }
+    let Some(PendingQuickload(mut scene)) = world.remove_resource::<PendingQuickload>() else {
+        return;
+    };
+
+    // Map every saved LDtk entity to its freshly spawned counterpart. Entities that are no
+    // longer in the level, e.g. because it was edited since, are left out.
+    let mut entity_map = EntityHashMap::default();
+    scene.entities.retain(|saved| {
+        let Some(iid) = saved.components.iter().find_map(|component| {
+            let is_iid = component
+                .get_represented_type_info()
+                .is_some_and(|info| info.type_id() == TypeId::of::<EntityIid>());
+            is_iid
+                .then(|| EntityIid::from_reflect(component.as_partial_reflect()))
+                .flatten()
+        }) else {
+            return true;
+        };
+        match live.iter().find(|(_, live_iid)| *live_iid == iid) {
+            Some((entity, _)) => {
+                entity_map.insert(saved.entity, *entity);
+                true
+            }
+            None => {
+                warn!(
+                    "The quicksaved entity {} is no longer in the level, skipping it",
+                    iid.as_str()
+                );
+                false
+            }
+        }
+    });
+
+    let level_entity_count = entity_map.len();
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    if let Err(e) = scene.write_to_world_with(world, &mut entity_map, &registry) {
+        error!("Failed to restore the quicksave: {e}");
+        return;
+    }
+
+    // Entities that didn't come from LDtk were spawned with only their saved components.
+    let spawned_balls: Vec<Entity> = entity_map
+        .values()
+        .copied()
+        .filter(|entity| {
+            world.get::<Ball>(*entity).is_some() && world.get::<Mesh2d>(*entity).is_none()
+        })
+        .collect();
+    world.resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
+        world.resource_scope(|world, mut materials: Mut<Assets<ColorMaterial>>| {
+            for entity in &spawned_balls {
+                world
+                    .entity_mut(*entity)
+                    .insert(ball_visuals(&mut meshes, &mut materials));
+            }
+        });
+    });
+    info!(
+        "Quickloaded {level_entity_count} level entities and {} balls",
+        spawned_balls.len()
+    );
+}
@@ -0,0 +1,287 @@
+//! Replays of the player's input, recorded to a file and played back.
+//!
+//! When a [`Replay`] resource is present, the player's moves and food drops are either
+//! recorded, or played back instead of the player's input until the replay runs out. Inputs
+//! are keyed on the fixed tick they happened after, so the gameplay sees them at the same
+//! time whatever the frame rate.
+
+use std::{
+    fmt::Write as _,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    game::{
+        fish_level::{
+            Ball, BallSpawnTimer, FoodDropped, LevelProject, PlayerMarker, ball_visuals,
+            player_control,
+        },
+        level_config::LevelProgress,
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), reset_replay)
+        .add_systems(
+            FixedPreUpdate,
+            play_inputs.run_if(in_state(Screen::Gameplay).and(is_playing)),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            count_tick.run_if(in_state(Screen::Gameplay).and(resource_exists::<Replay>)),
+        )
+        .add_systems(
+            Update,
+            record_inputs
+                .after(player_control)
+                .run_if(in_state(Screen::Gameplay).and(is_recording)),
+        )
+        .add_systems(OnExit(Screen::Gameplay), write_replay.run_if(is_recording))
+        .add_systems(
+            Last,
+            write_replay.run_if(is_recording.and(on_message::<AppExit>)),
+        );
+}
+
+/// What a [`Replay`] does with the player's input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Write the player's input to the replay file.
+    Record,
+    /// Play the input read from the replay file instead of the player's.
+    Play,
+}
+
+/// The player's input since [`Screen::Gameplay`] was entered.
+#[derive(Resource, Debug, Clone)]
+pub struct Replay {
+    mode: ReplayMode,
+    path: PathBuf,
+    /// The level the input was recorded on.
+    level: String,
+    inputs: Vec<ReplayInput>,
+    /// Fixed ticks since the player was spawned.
+    tick: u64,
+    /// The next input to play.
+    next: usize,
+}
+
+/// Where the player was after a fixed tick, and where they dropped food, if they did.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ReplayInput {
+    tick: u64,
+    x: f32,
+    drop: Option<Vec2>,
+}
+
+impl Replay {
+    /// Records the input to `path` when leaving gameplay or exiting the app.
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: ReplayMode::Record,
+            path: path.into(),
+            level: String::new(),
+            inputs: Vec::new(),
+            tick: 0,
+            next: 0,
+        }
+    }
+
+    /// Plays a replay previously written by [`Replay::record`].
+    pub fn play(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let (level, inputs) = read_replay(&path)?;
+        Ok(Self {
+            mode: ReplayMode::Play,
+            path,
+            level,
+            inputs,
+            tick: 0,
+            next: 0,
+        })
+    }
+
+    /// The LDtk project the replay was recorded on, empty if it isn't known yet.
+    pub fn level(&self) -> &str {
+        &self.level
+    }
+}
+
+fn read_replay(path: &Path) -> io::Result<(String, Vec<ReplayInput>)> {
+    let invalid = |line: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid replay line `{line}`"),
+        )
+    };
+    let contents = fs::read_to_string(path)?;
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let level = lines
+        .next()
+        .and_then(|line| line.strip_prefix("level "))
+        .ok_or_else(|| invalid("the first line must be `level <PATH>`"))?
+        .trim()
+        .to_string();
+    let inputs = lines
+        .map(|line| parse_input(line).ok_or_else(|| invalid(line)))
+        .collect::<io::Result<_>>()?;
+    Ok((level, inputs))
+}
+
+/// Parses `<TICK> <X>`, followed by `drop <X> <Y>` if food was dropped.
+fn parse_input(line: &str) -> Option<ReplayInput> {
+    let values: Vec<_> = line.split_whitespace().collect();
+    let number = |i: usize| values.get(i)?.parse::<f32>().ok();
+    let drop = match values.get(2) {
+        None => None,
+        Some(&"drop") => Some(Vec2::new(number(3)?, number(4)?)),
+        Some(_) => return None,
+    };
+    Some(ReplayInput {
+        tick: values.first()?.parse().ok()?,
+        x: number(1)?,
+        drop,
+    })
+}
+
+fn is_recording(replay: Option<Res<Replay>>) -> bool {
+    replay.is_some_and(|replay| replay.mode == ReplayMode::Record)
+}
+
+/// Whether the input is being played back. The player takes over once the replay runs out.
+pub(crate) fn is_playing(replay: Option<Res<Replay>>) -> bool {
+    replay
+        .is_some_and(|replay| replay.mode == ReplayMode::Play && replay.next < replay.inputs.len())
+}
+
+fn reset_replay(mut replay: Option<ResMut<Replay>>, project: Res<LevelProject>) {
+    let Some(replay) = &mut replay else {
+        return;
+    };
+    replay.tick = 0;
+    replay.next = 0;
+    if replay.mode == ReplayMode::Record {
+        replay.level = project.0.clone();
+        replay.inputs.clear();
+    }
+}
+
+fn count_tick(mut replay: ResMut<Replay>, player: Query<(), With<PlayerMarker>>) {
+    // Don't count the time spent spawning the level, it depends on the machine.
+    if !player.is_empty() {
+        replay.tick += 1;
+    }
+}
+
+fn play_inputs(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut replay: ResMut<Replay>,
+    player: Single<(&mut Transform, &mut BallSpawnTimer), With<PlayerMarker>>,
+    time: Res<Time>,
+    mut dropped: MessageWriter<FoodDropped>,
+    mut progress: ResMut<LevelProgress>,
+) {
+    let (mut player_tf, mut spawn_timer) = player.into_inner();
+    spawn_timer.0.tick(time.delta());
+
+    while let Some(input) = replay.inputs.get(replay.next).copied()
+        && input.tick <= replay.tick
+    {
+        replay.next += 1;
+        player_tf.translation.x = input.x;
+        if let Some(position) = input.drop
+            && progress.take_food()
+        {
+            let tf = Transform::from_translation(position.extend(10.0));
+            let ball = commands
+                .spawn((ball_visuals(&mut meshes, &mut materials), tf, Ball))
+                .id();
+            dropped.write(FoodDropped { ball });
+            spawn_timer.0.reset();
+        }
+    }
+}
+
+fn record_inputs(
+    mut replay: ResMut<Replay>,
+    player: Single<&Transform, With<PlayerMarker>>,
+    balls: Query<&Transform, With<Ball>>,
+    mut dropped: MessageReader<FoodDropped>,
+) {
+    let tick = replay.tick;
+    let x = player.translation.x;
+    let drops: Vec<_> = dropped
+        .read()
+        .filter_map(|dropped| balls.get(dropped.ball).ok())
+        .map(|tf| Some(tf.translation.truncate()))
+        .collect();
+    let moved = replay.inputs.last().is_none_or(|last| last.x != x);
+    if drops.is_empty() && moved {
+        replay.inputs.push(ReplayInput {
+            tick,
+            x,
+            drop: None,
+        });
+    }
+    for drop in drops {
+        replay.inputs.push(ReplayInput { tick, x, drop });
+    }
+}
+
+fn write_replay(replay: Res<Replay>) {
+    let mut contents = format!("level {}\n", replay.level);
+    for input in &replay.inputs {
+        let _ = match input.drop {
+            Some(drop) => writeln!(
+                contents,
+                "{} {} drop {} {}",
+                input.tick, input.x, drop.x, drop.y
+            ),
+            None => writeln!(contents, "{} {}", input.tick, input.x),
+        };
+    }
+    match fs::write(&replay.path, contents) {
+        Ok(()) => info!(
+            "Wrote {} inputs to {}",
+            replay.inputs.len(),
+            replay.path.display()
+        ),
+        Err(e) => error!(
+            "Failed to write the replay to {}: {e}",
+            replay.path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_moves_and_drops() {
+        assert_eq!(
+            parse_input("12 184.5"),
+            Some(ReplayInput {
+                tick: 12,
+                x: 184.5,
+                drop: None,
+            })
+        );
+        assert_eq!(
+            parse_input("66 202.4 drop 10.4 208"),
+            Some(ReplayInput {
+                tick: 66,
+                x: 202.4,
+                drop: Some(Vec2::new(10.4, 208.0)),
+            })
+        );
+        assert_eq!(parse_input("66 202.4 drop 10.4"), None);
+        assert_eq!(parse_input("66"), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! Sound effects of the gameplay, played in response to gameplay messages.
+//!
+//! Each [`GameplaySound`] is played as described by the asset manifest's
+//! [`SoundSpec`](crate::audio::SoundSpec): its volume, its random pitch and volume variation,
+//! and a cooldown so rapid events don't stack the same sound. Sounds are panned towards where
+//! they happened in the tank.
+
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+use bevy_seedling::{prelude::PlaybackSettings, sample::AudioSample};
+use rand::Rng;
+
+use crate::{
+    asset_tracking::{AssetCollection, AssetManifest, ResourceHandles},
+    audio::{positional_sound_effect, sound_effect_with_volume},
+    game::fish_level::{FishFed, FoodDropped, FoodWasted, GoldMarker, PlayerMarker},
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SoundCooldowns>();
+    app.add_systems(
+        Update,
+        check_sound_specs.run_if(in_state(Screen::Loading).and(resource_exists::<AssetManifest>)),
+    );
+    app.add_systems(
+        Update,
+        play_gameplay_sounds.run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
+#[reflect(Resource)]
+pub struct GameplayAudioAssets {
+    #[dependency]
+    #[asset(group = "gameplay")]
+    pub eat: Handle<AudioSample>,
+    #[dependency]
+    #[asset(group = "gameplay")]
+    pub drop: Handle<AudioSample>,
+    #[dependency]
+    #[asset(group = "gameplay")]
+    pub miss: Handle<AudioSample>,
+    #[dependency]
+    #[asset(group = "gameplay")]
+    pub golden_fish: Handle<AudioSample>,
+}
+
+/// A sound played in response to gameplay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum GameplaySound {
+    /// A grey fish ate a food ball.
+    Eat,
+    /// The player dropped a food ball.
+    Drop,
+    /// A food ball sank to the bottom without being eaten.
+    Miss,
+    /// The golden fish ate a food ball.
+    GoldenFish,
+}
+
+impl GameplaySound {
+    const ALL: [Self; 4] = [Self::Eat, Self::Drop, Self::Miss, Self::GoldenFish];
+
+    /// The name of the sound in the asset manifest.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Eat => "eat",
+            Self::Drop => "drop",
+            Self::Miss => "miss",
+            Self::GoldenFish => "golden_fish",
+        }
+    }
+
+    pub fn sample(self, assets: &GameplayAudioAssets) -> Handle<AudioSample> {
+        match self {
+            Self::Eat => assets.eat.clone(),
+            Self::Drop => assets.drop.clone(),
+            Self::Miss => assets.miss.clone(),
+            Self::GoldenFish => assets.golden_fish.clone(),
+        }
+    }
+}
+
+/// Fails the loading of the gameplay if the manifest doesn't say how to play a sound.
+fn check_sound_specs(manifest: Res<AssetManifest>, mut resource_handles: ResMut<ResourceHandles>) {
+    for sound in GameplaySound::ALL {
+        if let Err(failure) = manifest.sound(sound.name()) {
+            resource_handles.report(failure, Some("gameplay"));
+        }
+    }
+}
+
+/// When each [`GameplaySound`] was last played, in real time.
+#[derive(Resource, Debug, Default)]
+struct SoundCooldowns(HashMap<GameplaySound, Duration>);
+
+fn play_gameplay_sounds(
+    mut commands: Commands,
+    assets: If<Res<GameplayAudioAssets>>,
+    manifest: Res<AssetManifest>,
+    mut cooldowns: ResMut<SoundCooldowns>,
+    time: Res<Time<Real>>,
+    mut fed: MessageReader<FishFed>,
+    mut dropped: MessageReader<FoodDropped>,
+    mut wasted: MessageReader<FoodWasted>,
+    fish: Query<(&GlobalTransform, Has<GoldMarker>)>,
+    player: Query<&GlobalTransform, With<PlayerMarker>>,
+) {
+    let sounds = fed
+        .read()
+        .map(|FishFed { fish: entity }| match fish.get(*entity) {
+            Ok((transform, true)) => (GameplaySound::GoldenFish, Some(transform.translation())),
+            Ok((transform, false)) => (GameplaySound::Eat, Some(transform.translation())),
+            Err(_) => (GameplaySound::Eat, None),
+        })
+        // The ball was just spawned, it's where the player is.
+        .chain(dropped.read().map(|_| {
+            let position = player.single().ok().map(GlobalTransform::translation);
+            (GameplaySound::Drop, position)
+        }))
+        .chain(
+            wasted
+                .read()
+                .map(|wasted| (GameplaySound::Miss, Some(wasted.position))),
+        );
+
+    let now = time.elapsed();
+    // Not the `GameRng`: how many sounds play depends on real time, which would make the
+    // gameplay differ between runs of the same seed.
+    let mut rng = rand::rng();
+    for (sound, position) in sounds {
+        // Missing specs were reported while loading.
+        let Ok(spec) = manifest.sound(sound.name()) else {
+            continue;
+        };
+        if let Some(last) = cooldowns.0.get(&sound)
+            && now.saturating_sub(*last) < spec.cooldown()
+        {
+            continue;
+        }
+        cooldowns.0.insert(sound, now);
+
+        let volume = spec.volume + rng.random_range(-spec.volume_variation..=spec.volume_variation);
+        let speed = 1.0 + rng.random_range(-spec.pitch_variation..=spec.pitch_variation);
+        let sample = sound.sample(&assets);
+        let volume = volume.max(0.0);
+        let mut entity = match position {
+            Some(position) => commands.spawn(positional_sound_effect(sample, volume, position)),
+            None => commands.spawn(sound_effect_with_volume(sample, volume)),
+        };
+        entity.insert(PlaybackSettings::default().with_speed(speed));
+    }
+}
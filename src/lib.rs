@@ -0,0 +1,302 @@
+// Support configuring Bevy lints within code.
+#![cfg_attr(bevy_lint, feature(register_tool), register_tool(bevy))]
+
+pub mod archive;
+mod asset_tracking;
+mod audio;
+pub mod cli;
+#[cfg(feature = "dev")]
+mod dev_tools;
+#[cfg(feature = "editor")]
+mod editor;
+pub mod game;
+mod macros;
+mod menus;
+pub mod save;
+pub mod screens;
+pub mod settings;
+mod theme;
+
+use std::time::Duration;
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    asset::AssetMetaCheck,
+    prelude::*,
+    render::{RenderPlugin, settings::WgpuSettings},
+    window::{ExitCondition, MonitorSelection, WindowMode},
+    winit::WinitPlugin,
+};
+use bevy_pancam::{PanCam, PanCamPlugin};
+use bevy_seedling::{SeedlingPlugin, sample::AudioSample};
+
+use crate::{
+    game::{GameRng, checksum::ChecksumLog, fish_level::LevelProject, replay::Replay},
+    save::SaveProfile,
+    screens::Screen,
+    settings::Settings,
+};
+
+/// The whole game. Add it to an empty [`App`] to run it.
+///
+/// Use the builder methods to embed the game in tests, tools or alternative binaries, e.g.
+/// `AppPlugin::new().headless(true).start_screen(Screen::Gameplay)`.
+pub struct AppPlugin {
+    headless: bool,
+    editor: bool,
+    dev_tools: bool,
+    start_screen: Screen,
+    level: Option<String>,
+    seed: Option<u64>,
+    window_size: Option<UVec2>,
+    fullscreen: bool,
+    checksum_log: Option<ChecksumLog>,
+    replay: Option<Replay>,
+}
+
+impl Default for AppPlugin {
+    fn default() -> Self {
+        Self {
+            headless: false,
+            editor: true,
+            dev_tools: true,
+            start_screen: Screen::default(),
+            level: None,
+            seed: None,
+            window_size: None,
+            fullscreen: false,
+            checksum_log: None,
+            replay: None,
+        }
+    }
+}
+
+impl AppPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run without a window, a renderer or an audio device, e.g. for simulations.
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
+    /// Include the in-game editor. Only has an effect with the `editor` feature.
+    pub fn editor(mut self, editor: bool) -> Self {
+        self.editor = editor;
+        self
+    }
+
+    /// Include the development tools. Only has an effect with the `dev` feature.
+    pub fn dev_tools(mut self, dev_tools: bool) -> Self {
+        self.dev_tools = dev_tools;
+        self
+    }
+
+    /// The [`Screen`] to start on instead of the splash screen.
+    pub fn start_screen(mut self, screen: Screen) -> Self {
+        self.start_screen = screen;
+        self
+    }
+
+    /// The LDtk project to play, relative to the `assets` folder.
+    pub fn level(mut self, path: impl Into<String>) -> Self {
+        self.level = Some(path.into());
+        self
+    }
+
+    /// Seed the [`GameRng`] instead of picking a random seed.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The initial window size, in logical pixels.
+    pub fn window_size(mut self, size: UVec2) -> Self {
+        self.window_size = Some(size);
+        self
+    }
+
+    /// Start in borderless fullscreen on the primary monitor.
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// Record or verify per-tick gameplay checksums.
+    pub fn checksum_log(mut self, log: ChecksumLog) -> Self {
+        self.checksum_log = Some(log);
+        self
+    }
+
+    /// Record the player's input, or play it back.
+    pub fn replay(mut self, replay: Replay) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+}
+
+impl Plugin for AppPlugin {
+    fn build(&self, app: &mut App) {
+        // Headless apps don't depend on the player's settings.
+        let settings = if self.headless {
+            Settings::default()
+        } else {
+            Settings::load()
+        };
+
+        // Read packed assets in release builds, where there may be no `assets` folder. Asset
+        // sources must be registered before the `AssetPlugin`.
+        #[cfg(not(any(feature = "dev", target_family = "wasm")))]
+        archive::register_asset_source(app);
+
+        // Add Bevy plugins.
+        let default_plugins = DefaultPlugins.set(AssetPlugin {
+            // Wasm builds will check for meta files (that don't exist) if this isn't set.
+            // This causes errors and even panics on web build on itch.
+            // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
+            meta_check: AssetMetaCheck::Never,
+            ..default()
+        });
+        if self.headless {
+            app.add_plugins((
+                default_plugins
+                    .set(WindowPlugin {
+                        primary_window: None,
+                        exit_condition: ExitCondition::DontExit,
+                        ..default()
+                    })
+                    .set(RenderPlugin {
+                        render_creation: WgpuSettings {
+                            backends: None,
+                            ..default()
+                        }
+                        .into(),
+                        ..default()
+                    })
+                    .disable::<WinitPlugin>(),
+                ScheduleRunnerPlugin::run_loop(Duration::ZERO),
+                PanCamPlugin,
+            ));
+            // Audio samples are still loaded by asset collections, they just never play.
+            app.init_asset::<AudioSample>()
+                .register_asset_loader(audio::SilentSampleLoader);
+        } else {
+            app.add_plugins((
+                default_plugins.set(WindowPlugin {
+                    primary_window: Window {
+                        title: "Dreamadness".to_string(),
+                        fit_canvas_to_parent: true,
+                        resolution: self.window_size.map(Into::into).unwrap_or_default(),
+                        mode: if self.fullscreen {
+                            WindowMode::BorderlessFullscreen(MonitorSelection::Primary)
+                        } else {
+                            WindowMode::Windowed
+                        },
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                }),
+                PanCamPlugin,
+                SeedlingPlugin::default(),
+            ));
+        }
+
+        // Add other plugins. Resources inserted before them aren't overwritten by their defaults.
+        app.insert_state(self.start_screen);
+        app.insert_resource(settings);
+        if !self.headless {
+            app.insert_resource(SaveProfile::load());
+        }
+        if let Some(level) = &self.level {
+            app.insert_resource(LevelProject(level.clone()));
+        }
+        if let Some(seed) = self.seed {
+            app.insert_resource(GameRng::from_seed(seed));
+        }
+        if let Some(log) = &self.checksum_log {
+            app.insert_resource(log.clone());
+        }
+        if let Some(replay) = &self.replay {
+            app.insert_resource(replay.clone());
+        }
+        app.add_plugins((
+            asset_tracking::plugin,
+            audio::plugin,
+            game::plugin,
+            menus::plugin,
+            save::plugin,
+            screens::plugin,
+            settings::plugin,
+            theme::plugin,
+        ));
+        if self.dev_tools {
+            #[cfg(feature = "dev")]
+            app.add_plugins(dev_tools::plugin);
+        }
+        if self.editor {
+            #[cfg(feature = "editor")]
+            app.add_plugins(editor::plugin);
+        }
+
+        // Order new `AppSystems` variants by adding them here:
+        app.configure_sets(
+            Update,
+            (
+                AppSystems::TickTimers,
+                AppSystems::RecordInput,
+                AppSystems::Update,
+            )
+                .chain(),
+        );
+
+        // Set up the `Pause` state.
+        app.init_state::<Pause>();
+        app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
+        app.configure_sets(
+            FixedPostUpdate,
+            PausableSystems.run_if(in_state(Pause(false))),
+        );
+
+        // Spawn the main camera.
+        app.add_systems(Startup, spawn_camera);
+    }
+}
+
+/// High-level groupings of systems for the app in the `Update` schedule.
+/// When adding a new variant, make sure to order it in the `configure_sets`
+/// call above.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+enum AppSystems {
+    /// Tick timers.
+    TickTimers,
+    /// Record player input.
+    RecordInput,
+    /// Do everything else (consider splitting this into further variants).
+    Update,
+}
+
+/// Whether or not the game is paused.
+#[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+struct Pause(pub bool);
+
+/// A system set for systems that shouldn't run while the game is paused.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct PausableSystems;
+
+fn spawn_camera(mut commands: Commands) {
+    commands.spawn((
+        Name::from("Camera"),
+        Camera2d,
+        PanCam {
+            grab_buttons: vec![MouseButton::Right],
+            enabled: false,
+            zoom_to_cursor: false,
+            min_scale: 0.5,
+            max_scale: 5.0,
+            ..default()
+        },
+    ));
+}
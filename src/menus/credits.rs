@@ -3,7 +3,12 @@
 use bevy::{ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*};
 use bevy_seedling::sample::AudioSample;
 
-use crate::{asset_tracking::LoadResource, audio::music, menus::Menu, theme::prelude::*};
+use crate::{
+    asset_tracking::{AssetCollection, FallbackResource},
+    audio::{AppMusicExt, MusicCollection, Playlist},
+    menus::Menu,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Credits), spawn_credits_menu);
@@ -12,8 +17,7 @@ pub(super) fn plugin(app: &mut App) {
         go_back.run_if(in_state(Menu::Credits).and(input_just_pressed(KeyCode::Escape))),
     );
 
-    app.load_resource::<CreditsAssets>();
-    app.add_systems(OnEnter(Menu::Credits), start_credits_music);
+    app.add_music::<CreditsAssets>(Menu::Credits);
 }
 
 fn spawn_credits_menu(mut commands: Commands) {
@@ -87,26 +91,30 @@ fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Main);
 }
 
-#[derive(Resource, Asset, Clone, Reflect)]
+#[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
+#[asset_collection(fallback)]
 #[reflect(Resource)]
 pub struct CreditsAssets {
     #[dependency]
+    #[asset(group = "credits")]
     pub music: Handle<AudioSample>,
 }
 
-impl FromWorld for CreditsAssets {
-    fn from_world(world: &mut World) -> Self {
-        let assets = world.resource::<AssetServer>();
+impl FallbackResource for CreditsAssets {
+    /// The credits can roll in silence.
+    fn fallback(_: &mut World) -> Self {
         Self {
-            music: assets.load("audio/music/454600__euphrosyyn__crystallized-madness.wav"),
+            music: Handle::default(),
         }
     }
 }
 
-fn start_credits_music(mut commands: Commands, credits_music: Res<CreditsAssets>) {
-    commands.spawn((
-        Name::new("Credits Music"),
-        DespawnOnExit(Menu::Credits),
-        music(credits_music.music.clone()),
-    ));
+impl MusicCollection for CreditsAssets {
+    fn playlist(&self) -> Playlist {
+        // The fallback has no music, the music from before fades out.
+        if self.music == Handle::default() {
+            return Playlist::default();
+        }
+        Playlist::single(self.music.clone())
+    }
 }
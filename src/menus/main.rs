@@ -1,47 +1,75 @@
 //! The main menu (seen on the title screen).
 
-use bevy::prelude::*;
+use bevy::{ecs::spawn::SpawnWith, prelude::*};
 
-use crate::{asset_tracking::ResourceHandles, menus::Menu, screens::Screen, theme::widget};
+#[cfg(feature = "editor")]
+use crate::{asset_tracking::ResourceHandles, editor::EditorToggle};
+use crate::{
+    game::fish_level::LevelProject, menus::Menu, save::SaveProfile, screens::Screen, theme::widget,
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Main), spawn_main_menu);
 }
 
-fn spawn_main_menu(mut commands: Commands) {
+fn spawn_main_menu(
+    mut commands: Commands,
+    profile: Option<Res<SaveProfile>>,
+    #[cfg(feature = "editor")] editor: Option<Res<State<EditorToggle>>>,
+) {
+    // The editor is only available when its plugin was added.
+    #[cfg(feature = "editor")]
+    let editor = editor.is_some() && cfg!(not(target_family = "wasm"));
+    let can_continue = profile.is_some_and(|profile| profile.has_progress());
     commands.spawn((
         widget::ui_root("Main Menu"),
         GlobalZIndex(2),
         DespawnOnExit(Menu::Main),
-        #[cfg(not(target_family = "wasm"))]
-        children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
-            widget::button("Editor", enter_loading_or_editor_screen),
-            widget::button("Settings", open_settings_menu),
-            widget::button("Credits", open_credits_menu),
-            widget::button("Exit", exit_app),
-        ],
-        #[cfg(target_family = "wasm")]
-        children![
-            widget::button("Play", enter_loading_or_gameplay_screen),
-            widget::button("Settings", open_settings_menu),
-            widget::button("Credits", open_credits_menu),
-        ],
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            if can_continue {
+                parent.spawn(widget::button("Continue", continue_game));
+            }
+            parent.spawn(widget::button("Play", play));
+            #[cfg(feature = "editor")]
+            if editor {
+                parent.spawn(widget::button("Editor", enter_loading_or_editor_screen));
+            }
+            parent.spawn(widget::button("Settings", open_settings_menu));
+            parent.spawn(widget::button("Credits", open_credits_menu));
+            #[cfg(not(target_family = "wasm"))]
+            parent.spawn(widget::button("Exit", exit_app));
+        })),
     ));
 }
 
-fn enter_loading_or_gameplay_screen(
+/// Starts the campaign from its first level, even after "Continue" picked another one.
+fn play(
     _: On<Pointer<Click>>,
-    resource_handles: Res<ResourceHandles>,
+    mut project: ResMut<LevelProject>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
-    if resource_handles.is_all_done() {
-        next_screen.set(Screen::Gameplay);
-    } else {
-        next_screen.set(Screen::Loading);
+    project.set_if_neq(LevelProject::default());
+    // The gameplay assets are loaded on the loading screen, which goes straight through if
+    // they already are.
+    next_screen.set(Screen::Loading);
+}
+
+/// Resumes from the last level the player unlocked.
+fn continue_game(
+    _: On<Pointer<Click>>,
+    profile: Res<SaveProfile>,
+    mut project: ResMut<LevelProject>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if let Some(level) = profile.continue_level() {
+        project.0 = level.to_string();
     }
+    // The level may not even have started loading yet, the loading screen goes straight
+    // through if it has finished.
+    next_screen.set(Screen::Loading);
 }
 
+#[cfg(feature = "editor")]
 fn enter_loading_or_editor_screen(
     _: On<Pointer<Click>>,
     resource_handles: Res<ResourceHandles>,
@@ -1,10 +1,11 @@
 //! The settings menu.
 //!
-//! Additional settings and accessibility options should go here.
+//! Additional settings and accessibility options should go here. Changes are saved to disk by
+//! the [`Settings`] resource.
 
-use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*};
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
-use crate::{menus::Menu, screens::Screen, theme::prelude::*};
+use crate::{menus::Menu, screens::Screen, settings::Settings, theme::prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
@@ -15,7 +16,7 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        (update_volume_labels, update_toggle_labels).run_if(in_state(Menu::Settings)),
     );
 }
 
@@ -43,27 +44,71 @@ fn settings_grid() -> impl Bundle {
             ..default()
         },
         children![
-            (
-                widget::label("Master Volume"),
-                Node {
-                    justify_self: JustifySelf::End,
-                    ..default()
-                }
-            ),
-            global_volume_widget(),
+            setting_label("Master Volume"),
+            volume_widget(VolumeSetting::Master),
+            setting_label("Music Volume"),
+            volume_widget(VolumeSetting::Music),
+            setting_label("Mute Music"),
+            toggle_widget(ToggleSetting::MusicMuted),
+            setting_label("Sound Volume"),
+            volume_widget(VolumeSetting::Sfx),
+            setting_label("Mute Sounds"),
+            toggle_widget(ToggleSetting::SfxMuted),
         ],
     )
 }
 
-fn global_volume_widget() -> impl Bundle {
+fn setting_label(text: &'static str) -> impl Bundle {
     (
-        Name::new("Global Volume Widget"),
+        widget::label(text),
+        Node {
+            justify_self: JustifySelf::End,
+            ..default()
+        },
+    )
+}
+
+/// One of the volume sliders of the settings menu.
+#[derive(Reflect, Clone, Copy, Debug)]
+enum VolumeSetting {
+    Master,
+    Music,
+    Sfx,
+}
+
+impl VolumeSetting {
+    fn get(self, settings: &Settings) -> f32 {
+        match self {
+            Self::Master => settings.master_volume,
+            Self::Music => settings.music_volume,
+            Self::Sfx => settings.sfx_volume,
+        }
+    }
+
+    fn get_mut(self, settings: &mut Settings) -> &mut f32 {
+        match self {
+            Self::Master => &mut settings.master_volume,
+            Self::Music => &mut settings.music_volume,
+            Self::Sfx => &mut settings.sfx_volume,
+        }
+    }
+}
+
+fn volume_widget(setting: VolumeSetting) -> impl Bundle {
+    (
+        Name::new(format!("{setting:?} Volume Widget")),
         Node {
             justify_self: JustifySelf::Start,
             ..default()
         },
         children![
-            widget::button_small("-", lower_global_volume),
+            widget::button_small(
+                "-",
+                move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let volume = setting.get_mut(&mut settings);
+                    *volume = (*volume - 0.1).max(MIN_VOLUME);
+                }
+            ),
             (
                 Name::new("Current Volume"),
                 Node {
@@ -71,9 +116,15 @@ fn global_volume_widget() -> impl Bundle {
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
-                children![(widget::label(""), GlobalVolumeLabel)],
+                children![(widget::label(""), VolumeLabel(setting))],
+            ),
+            widget::button_small(
+                "+",
+                move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+                    let volume = setting.get_mut(&mut settings);
+                    *volume = (*volume + 0.1).min(MAX_VOLUME);
+                }
             ),
-            widget::button_small("+", raise_global_volume),
         ],
     )
 }
@@ -81,26 +132,77 @@ fn global_volume_widget() -> impl Bundle {
 const MIN_VOLUME: f32 = 0.0;
 const MAX_VOLUME: f32 = 3.0;
 
-fn lower_global_volume(_: On<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() - 0.1).max(MIN_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct VolumeLabel(VolumeSetting);
+
+fn update_volume_labels(settings: Res<Settings>, mut labels: Query<(&mut Text, &VolumeLabel)>) {
+    for (mut text, label) in &mut labels {
+        let percent = 100.0 * label.0.get(&settings);
+        text.0 = format!("{percent:3.0}%");
+    }
+}
+
+/// One of the on/off settings of the settings menu.
+#[derive(Reflect, Clone, Copy, Debug)]
+enum ToggleSetting {
+    MusicMuted,
+    SfxMuted,
+}
+
+impl ToggleSetting {
+    fn get(self, settings: &Settings) -> bool {
+        match self {
+            Self::MusicMuted => settings.music_muted,
+            Self::SfxMuted => settings.sfx_muted,
+        }
+    }
+
+    fn get_mut(self, settings: &mut Settings) -> &mut bool {
+        match self {
+            Self::MusicMuted => &mut settings.music_muted,
+            Self::SfxMuted => &mut settings.sfx_muted,
+        }
+    }
 }
 
-fn raise_global_volume(_: On<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
-    let linear = (global_volume.volume.to_linear() + 0.1).min(MAX_VOLUME);
-    global_volume.volume = Volume::Linear(linear);
+fn toggle_widget(setting: ToggleSetting) -> impl Bundle {
+    // Like the volumes, "-" turns it off and "+" turns it on.
+    let set = move |on: bool| {
+        move |_: On<Pointer<Click>>, mut settings: ResMut<Settings>| {
+            *setting.get_mut(&mut settings) = on;
+        }
+    };
+    (
+        Name::new(format!("{setting:?} Widget")),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("-", set(false)),
+            (
+                Name::new("Current Value"),
+                Node {
+                    padding: UiRect::horizontal(px(10)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), ToggleLabel(setting))],
+            ),
+            widget::button_small("+", set(true)),
+        ],
+    )
 }
 
 #[derive(Component, Reflect)]
 #[reflect(Component)]
-struct GlobalVolumeLabel;
+struct ToggleLabel(ToggleSetting);
 
-fn update_global_volume_label(
-    global_volume: Res<GlobalVolume>,
-    mut label: Single<&mut Text, With<GlobalVolumeLabel>>,
-) {
-    let percent = 100.0 * global_volume.volume.to_linear();
-    label.0 = format!("{percent:3.0}%");
+fn update_toggle_labels(settings: Res<Settings>, mut labels: Query<(&mut Text, &ToggleLabel)>) {
+    for (mut text, label) in &mut labels {
+        text.0 = if label.0.get(&settings) { "On" } else { "Off" }.to_string();
+    }
 }
 
 fn go_back_on_click(
@@ -0,0 +1,388 @@
+//! The player's save profile: campaign progression and statistics, persisted between sessions.
+//!
+//! The profile is stored as a versioned RON file. Older files are upgraded on load by running
+//! them through [`MIGRATIONS`], so bump [`SAVE_VERSION`] and add a migration whenever the
+//! format of [`SaveProfile`] changes.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use ron::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    PausableSystems,
+    game::{
+        fish_level::{Ball, FishFed, FoodWasted, KeyDrop, LEVELS, LevelProject},
+        level_config::{LevelCompleted, LevelConfig},
+    },
+    screens::Screen,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    // `AppPlugin` only inserts the profile for interactive sessions, so simulations and other
+    // tools never touch the player's save.
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        start_attempt.run_if(resource_exists::<SaveProfile>),
+    );
+    app.add_systems(
+        FixedPostUpdate,
+        (
+            tick_attempt.in_set(PausableSystems),
+            track_attempt,
+            collect_keys,
+            record_completion,
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<SaveProfile>)),
+    );
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        record_statistics.run_if(resource_exists::<SaveProfile>),
+    );
+}
+
+/// The current version of the save file format.
+pub const SAVE_VERSION: u32 = 1;
+
+/// Upgrades a save file from version `n` to version `n + 1`, where `n` is the index in the
+/// list. There must be exactly `SAVE_VERSION` of them.
+const MIGRATIONS: &[fn(Value) -> io::Result<Value>] = &[group_statistics];
+
+/// Version 0 is the format of the first builds: it has no `version`, and the statistics are
+/// fields of the profile itself.
+fn group_statistics(value: Value) -> io::Result<Value> {
+    let Value::Map(mut profile) = value else {
+        return Err(invalid_data("the profile isn't a map"));
+    };
+    let mut statistics = ron::Map::new();
+    for field in [
+        "play_time_secs",
+        "levels_completed",
+        "fish_fed",
+        "food_dropped",
+        "food_wasted",
+    ] {
+        if let Some(value) = profile.remove(&Value::from(field)) {
+            statistics.insert(Value::from(field), value);
+        }
+    }
+    profile.insert(Value::from("statistics"), Value::Map(statistics));
+    Ok(Value::Map(profile))
+}
+
+/// Progression and statistics of the player, across every session.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct SaveProfile {
+    /// The format version the profile was saved with. Always [`SAVE_VERSION`] once loaded.
+    pub version: u32,
+    /// LDtk projects the player can play, in the order they were unlocked.
+    pub unlocked_levels: Vec<String>,
+    /// The best completion of each level, by LDtk project.
+    pub best_scores: BTreeMap<String, LevelScore>,
+    /// Identifiers of the keys collected so far, dropped by fish with a `Key` field in LDtk.
+    pub keys: BTreeSet<String>,
+    pub statistics: Statistics,
+}
+
+impl Default for SaveProfile {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            unlocked_levels: vec![LEVELS[0].to_string()],
+            best_scores: BTreeMap::new(),
+            keys: BTreeSet::new(),
+            statistics: Statistics::default(),
+        }
+    }
+}
+
+/// How well a level was completed. Faster is better.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelScore {
+    pub time_secs: f32,
+    pub food_wasted: u32,
+}
+
+impl LevelScore {
+    fn is_better_than(&self, other: &Self) -> bool {
+        (self.time_secs, self.food_wasted) < (other.time_secs, other.food_wasted)
+    }
+}
+
+/// Totals across every level attempt.
+#[derive(Debug, Clone, PartialEq, Default, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    pub play_time_secs: f64,
+    pub levels_completed: u32,
+    pub fish_fed: u32,
+    pub food_dropped: u32,
+    pub food_wasted: u32,
+}
+
+impl SaveProfile {
+    /// Loads the profile from the save file, falling back to a new profile if there is none.
+    ///
+    /// A save file that can't be read is moved aside instead of being overwritten, so the
+    /// player's progress can still be recovered by hand.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read the save file {}: {e}", path.display());
+                return Self::default();
+            }
+        };
+        match Self::from_ron(&contents) {
+            Ok(profile) => profile,
+            Err(e) => {
+                let backup = path.with_extension("ron.bak");
+                warn!(
+                    "Ignoring invalid save file {}, moving it to {}: {e}",
+                    path.display(),
+                    backup.display()
+                );
+                if let Err(e) = fs::rename(&path, &backup) {
+                    error!("Failed to move the save file: {e}");
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Parses a save file of any supported version.
+    pub fn from_ron(contents: &str) -> io::Result<Self> {
+        let mut value: Value = ron::from_str(contents).map_err(invalid_data)?;
+        let version = match &value {
+            Value::Map(map) => match map.get(&Value::from("version")) {
+                Some(version) => version
+                    .clone()
+                    .into_rust::<u32>()
+                    .map_err(|_| invalid_data("invalid save version"))?,
+                None => 0,
+            },
+            _ => return Err(invalid_data("the profile isn't a map")),
+        };
+
+        if version > SAVE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported save version {version}, expected at most {SAVE_VERSION}"
+            )));
+        }
+        for migration in &MIGRATIONS[version as usize..] {
+            value = migration(value)?;
+        }
+        let mut profile: Self = value.into_rust().map_err(invalid_data)?;
+        profile.version = SAVE_VERSION;
+        Ok(profile)
+    }
+
+    /// Writes the profile to the save file.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let profile = Self {
+            version: SAVE_VERSION,
+            ..self.clone()
+        };
+        let contents = ron::ser::to_string_pretty(&profile, ron::ser::PrettyConfig::default())
+            .map_err(io::Error::other)?;
+        write_atomically(&path, contents)
+    }
+
+    /// The save file, in the platform's data directory. There is none on the web.
+    fn path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join("dreamadness").join("save.ron"))
+    }
+
+    /// The level the "Continue" button resumes from.
+    pub fn continue_level(&self) -> Option<&str> {
+        self.unlocked_levels.last().map(String::as_str)
+    }
+
+    /// Whether the player has played at all, i.e. there is something to continue.
+    pub fn has_progress(&self) -> bool {
+        self.statistics.play_time_secs > 0.0
+    }
+
+    /// Records a completion of `level`, keeping the best score and unlocking `next`.
+    pub fn complete_level(&mut self, level: &str, next: Option<&str>, score: LevelScore) {
+        self.statistics.levels_completed += 1;
+        let best = self.best_scores.entry(level.to_string()).or_insert(score);
+        if score.is_better_than(best) {
+            *best = score;
+        }
+
+        if let Some(next) = next
+            && !self.unlocked_levels.iter().any(|l| l == next)
+        {
+            info!("Unlocked {next}");
+            self.unlocked_levels.push(next.to_string());
+        }
+    }
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Writes to a temporary file first, so a crash mid-write can't corrupt the save.
+pub(crate) fn write_atomically(path: &Path, contents: String) -> io::Result<()> {
+    let tmp = path.with_extension("ron.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+/// Progress of the level currently being played.
+#[derive(Resource, Debug, Default, Hash, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct LevelAttempt {
+    ticks: u32,
+    feedings: u32,
+    food_dropped: u32,
+    food_wasted: u32,
+}
+
+fn start_attempt(mut commands: Commands) {
+    commands.insert_resource(LevelAttempt::default());
+}
+
+/// Time spent paused doesn't count as play time.
+fn tick_attempt(mut attempt: ResMut<LevelAttempt>) {
+    attempt.ticks += 1;
+}
+
+fn track_attempt(
+    mut attempt: ResMut<LevelAttempt>,
+    mut fed: MessageReader<FishFed>,
+    mut wasted: MessageReader<FoodWasted>,
+    dropped: Query<(), Added<Ball>>,
+) {
+    attempt.feedings += fed.read().count() as u32;
+    attempt.food_wasted += wasted.read().count() as u32;
+    attempt.food_dropped += dropped.iter().count() as u32;
+}
+
+/// Feeding a fish that drops a key collects it. Keys are saved with the rest of the profile.
+fn collect_keys(
+    mut fed: MessageReader<FishFed>,
+    keys: Query<&KeyDrop>,
+    mut profile: ResMut<SaveProfile>,
+) {
+    for FishFed { fish } in fed.read() {
+        if let Ok(KeyDrop(Some(key))) = keys.get(*fish)
+            && profile.keys.insert(key.clone())
+        {
+            info!("Collected the key {key}");
+        }
+    }
+}
+
+/// A level is completed once the objective of its [`LevelConfig`] is met.
+fn record_completion(
+    mut completed: MessageReader<LevelCompleted>,
+    attempt: Res<LevelAttempt>,
+    mut profile: ResMut<SaveProfile>,
+    project: Res<LevelProject>,
+    config: Res<LevelConfig>,
+) {
+    for LevelCompleted { time_secs } in completed.read() {
+        let score = LevelScore {
+            time_secs: *time_secs,
+            food_wasted: attempt.food_wasted,
+        };
+        info!("Completed {} in {:.1}s", project.0, score.time_secs);
+        profile.complete_level(&project.0, config.next_level(&project.0), score);
+        if let Err(e) = profile.save() {
+            error!("Failed to save the game: {e}");
+        }
+    }
+}
+
+fn record_statistics(
+    mut commands: Commands,
+    attempt: Option<Res<LevelAttempt>>,
+    mut profile: ResMut<SaveProfile>,
+    time: Res<Time<Fixed>>,
+) {
+    let Some(attempt) = attempt else {
+        return;
+    };
+    commands.remove_resource::<LevelAttempt>();
+    let statistics = &mut profile.statistics;
+    statistics.play_time_secs += f64::from(attempt.ticks) * time.timestep().as_secs_f64();
+    statistics.fish_fed += attempt.feedings;
+    statistics.food_dropped += attempt.food_dropped;
+    statistics.food_wasted += attempt.food_wasted;
+    if let Err(e) = profile.save() {
+        error!("Failed to save the game: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_cover_every_version() {
+        assert_eq!(MIGRATIONS.len(), SAVE_VERSION as usize);
+    }
+
+    #[test]
+    fn upgrades_a_version_0_profile() {
+        let v0 = r#"(
+            unlocked_levels: ["levels/level_1.ldtk"],
+            best_scores: {
+                "levels/level_1.ldtk": (time_secs: 42.5, food_wasted: 3),
+            },
+            play_time_secs: 120.0,
+            levels_completed: 2,
+            fish_fed: 12,
+            food_dropped: 20,
+            food_wasted: 8,
+        )"#;
+        let profile = SaveProfile::from_ron(v0).unwrap();
+        assert_eq!(profile.version, SAVE_VERSION);
+        assert_eq!(profile.unlocked_levels, ["levels/level_1.ldtk"]);
+        assert_eq!(
+            profile.best_scores["levels/level_1.ldtk"],
+            LevelScore {
+                time_secs: 42.5,
+                food_wasted: 3,
+            }
+        );
+        assert_eq!(
+            profile.statistics,
+            Statistics {
+                play_time_secs: 120.0,
+                levels_completed: 2,
+                fish_fed: 12,
+                food_dropped: 20,
+                food_wasted: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let newer = format!("(version: {})", SAVE_VERSION + 1);
+        assert!(SaveProfile::from_ron(&newer).is_err());
+    }
+}
@@ -0,0 +1,48 @@
+//! The screen shown when assets the game needs failed to load.
+
+use bevy::{ecs::spawn::SpawnWith, prelude::*};
+
+use crate::{asset_tracking::ResourceHandles, screens::Screen, theme::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::LoadError), spawn_load_error_screen);
+    // Going back to the title gives the failed assets another chance.
+    app.add_systems(OnExit(Screen::LoadError), retry_failed_assets);
+}
+
+fn spawn_load_error_screen(mut commands: Commands, resource_handles: Res<ResourceHandles>) {
+    let failures: Vec<String> = resource_handles
+        .failures()
+        .map(|failure| format!("{}\n{}", failure.path, failure.error))
+        .collect();
+    commands.spawn((
+        widget::ui_root("Load Error Screen"),
+        DespawnOnExit(Screen::LoadError),
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent.spawn(widget::header("Failed to load the game"));
+            for failure in failures {
+                // Override the label's font size, a bundle can't hold the same component twice.
+                parent.spawn(widget::label(failure)).insert((
+                    TextFont::from_font_size(16.0),
+                    TextLayout::new_with_justify(Justify::Center),
+                ));
+            }
+            parent.spawn(widget::button("Back to title", quit_to_title));
+            #[cfg(not(target_family = "wasm"))]
+            parent.spawn(widget::button("Exit", exit_app));
+        })),
+    ));
+}
+
+fn retry_failed_assets(assets: Res<AssetServer>, mut resource_handles: ResMut<ResourceHandles>) {
+    resource_handles.retry_failed(&assets);
+}
+
+fn quit_to_title(_: On<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn exit_app(_: On<Pointer<Click>>, mut app_exit: MessageWriter<AppExit>) {
+    app_exit.write(AppExit::Success);
+}
@@ -10,7 +10,15 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        enter_gameplay_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)),
+        update_loading_screen.run_if(in_state(Screen::Loading)),
+    );
+    app.add_systems(
+        Update,
+        (
+            enter_gameplay_screen.run_if(all_assets_loaded),
+            enter_load_error_screen.run_if(any_asset_failed),
+        )
+            .run_if(in_state(Screen::Loading)),
     );
 }
 
@@ -18,14 +26,41 @@ fn spawn_loading_screen(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Loading Screen"),
         DespawnOnExit(Screen::Loading),
-        children![widget::label("Loading...")],
+        children![
+            (widget::label("Loading..."), LoadingLabel),
+            widget::progress_bar(0.0),
+        ],
     ));
 }
 
+#[derive(Component)]
+struct LoadingLabel;
+
+fn update_loading_screen(
+    resource_handles: Res<ResourceHandles>,
+    mut label: Single<&mut Text, With<LoadingLabel>>,
+    mut bar: Single<&mut ProgressBar>,
+) {
+    let progress = resource_handles.progress();
+    let text = format!("Loading... {}/{}", progress.loaded, progress.total);
+    if label.0 != text {
+        label.0 = text;
+    }
+    bar.set_if_neq(ProgressBar(progress.fraction()));
+}
+
 fn enter_gameplay_screen(mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Gameplay);
 }
 
+fn enter_load_error_screen(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::LoadError);
+}
+
 fn all_assets_loaded(resource_handles: Res<ResourceHandles>) -> bool {
     resource_handles.is_all_done()
 }
+
+fn any_asset_failed(resource_handles: Res<ResourceHandles>) -> bool {
+    resource_handles.failures().next().is_some()
+}
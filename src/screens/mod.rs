@@ -1,6 +1,7 @@
 //! The game's main screen states and transitions between them.
 
 mod gameplay;
+mod load_error;
 mod loading;
 mod splash;
 mod title;
@@ -8,10 +9,10 @@ mod title;
 use bevy::prelude::*;
 
 pub(super) fn plugin(app: &mut App) {
-    app.init_state::<Screen>();
-
+    // The initial `Screen` is inserted by `AppPlugin`.
     app.add_plugins((
         gameplay::plugin,
+        load_error::plugin,
         loading::plugin,
         splash::plugin,
         title::plugin,
@@ -25,6 +26,8 @@ pub enum Screen {
     Splash,
     Title,
     Loading,
+    LoadError,
     Gameplay,
+    #[cfg(feature = "editor")]
     Editor,
 }
@@ -6,12 +6,21 @@ use bevy::{
     prelude::*,
 };
 
-use crate::{AppSystems, screens::Screen, theme::prelude::*};
+use crate::{
+    AppSystems,
+    asset_tracking::{AssetManifest, ResourceHandles},
+    screens::Screen,
+    theme::prelude::*,
+};
 
 pub(super) fn plugin(app: &mut App) {
-    // Spawn splash screen.
+    // Spawn splash screen, once the asset manifest tells where its image is.
     app.insert_resource(ClearColor(SPLASH_BACKGROUND_COLOR));
-    app.add_systems(OnEnter(Screen::Splash), spawn_splash_screen);
+    app.add_systems(
+        Update,
+        (spawn_splash_screen, insert_splash_timer)
+            .run_if(in_state(Screen::Splash).and(resource_added::<AssetManifest>)),
+    );
 
     // Animate splash screen.
     app.add_systems(
@@ -24,7 +33,6 @@ pub(super) fn plugin(app: &mut App) {
     );
 
     // Add splash timer.
-    app.add_systems(OnEnter(Screen::Splash), insert_splash_timer);
     app.add_systems(OnExit(Screen::Splash), remove_splash_timer);
     app.add_systems(
         Update,
@@ -32,7 +40,13 @@ pub(super) fn plugin(app: &mut App) {
             tick_splash_timer.in_set(AppSystems::TickTimers),
             check_splash_timer.in_set(AppSystems::Update),
         )
-            .run_if(in_state(Screen::Splash)),
+            .run_if(in_state(Screen::Splash).and(resource_exists::<SplashTimer>)),
+    );
+
+    // Show what failed instead, e.g. the manifest itself, which the splash screen waits for.
+    app.add_systems(
+        Update,
+        enter_load_error_screen.run_if(in_state(Screen::Splash).and(any_asset_failed)),
     );
 
     // Exit the splash screen early if the player hits escape.
@@ -47,7 +61,25 @@ const SPLASH_BACKGROUND_COLOR: Color = Color::srgb(0.157, 0.157, 0.157);
 const SPLASH_DURATION_SECS: f32 = 1.8;
 const SPLASH_FADE_DURATION_SECS: f32 = 0.6;
 
-fn spawn_splash_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_splash_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    manifest: Res<AssetManifest>,
+    mut resource_handles: ResMut<ResourceHandles>,
+) {
+    let image = match manifest.path("splash", "image") {
+        // The `embedded_assets` feature embeds it for instant loading, but that is currently
+        // [broken on Windows Wasm builds](https://github.com/bevyengine/bevy/issues/14246).
+        Ok(path) => asset_server.load_with_settings(path, |settings: &mut ImageLoaderSettings| {
+            // Make an exception for the splash image in case
+            // `ImagePlugin::default_nearest()` is used for pixel art.
+            settings.sampler = ImageSampler::linear();
+        }),
+        Err(failure) => {
+            resource_handles.report(failure, None);
+            return;
+        }
+    };
     commands.spawn((
         widget::ui_root("Splash Screen"),
         BackgroundColor(SPLASH_BACKGROUND_COLOR),
@@ -59,16 +91,7 @@ fn spawn_splash_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
                 width: percent(70),
                 ..default()
             },
-            ImageNode::new(asset_server.load_with_settings(
-                // This should be an embedded asset for instant loading, but that is
-                // currently [broken on Windows Wasm builds](https://github.com/bevyengine/bevy/issues/14246).
-                "images/splash.png",
-                |settings: &mut ImageLoaderSettings| {
-                    // Make an exception for the splash image in case
-                    // `ImagePlugin::default_nearest()` is used for pixel art.
-                    settings.sampler = ImageSampler::linear();
-                },
-            )),
+            ImageNode::new(image),
             ImageNodeFadeInOut {
                 total_duration: SPLASH_DURATION_SECS,
                 fade_duration: SPLASH_FADE_DURATION_SECS,
@@ -143,3 +166,11 @@ fn check_splash_timer(timer: ResMut<SplashTimer>, mut next_screen: ResMut<NextSt
 fn enter_title_screen(mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
+
+fn enter_load_error_screen(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::LoadError);
+}
+
+fn any_asset_failed(resource_handles: Res<ResourceHandles>) -> bool {
+    resource_handles.failures().next().is_some()
+}
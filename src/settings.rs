@@ -0,0 +1,89 @@
+//! Player settings, saved to a config file so they persist between launches.
+
+use std::{fs, io, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::save::write_atomically;
+
+pub(super) fn plugin(app: &mut App) {
+    // `AppPlugin` loads the settings before the audio plugins are added, so only later changes
+    // need to be saved. The audio module applies the volumes.
+    app.add_systems(
+        Update,
+        save_settings.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+    );
+}
+
+/// Settings chosen by the player in the settings menu.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    /// Linear master volume, between 0 and 3.
+    pub master_volume: f32,
+    /// Linear music volume, between 0 and 3, on top of the master volume.
+    pub music_volume: f32,
+    pub music_muted: bool,
+    /// Linear sound effect volume, between 0 and 3, on top of the master volume.
+    pub sfx_volume: f32,
+    pub sfx_muted: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            music_muted: false,
+            sfx_volume: 1.0,
+            sfx_muted: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings from the config file, falling back to the defaults if there is none
+    /// or it can't be read.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid settings in {}: {e}", path.display());
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Failed to read settings from {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes the settings to the config file.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(io::Error::other)?;
+        write_atomically(&path, contents)
+    }
+
+    /// The config file, in the platform's config directory. There is none on the web.
+    fn path() -> Option<PathBuf> {
+        Some(dirs::config_dir()?.join("dreamadness").join("settings.ron"))
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if let Err(e) = settings.save() {
+        error!("Failed to save settings: {e}");
+    }
+}
@@ -1,14 +1,13 @@
 use bevy::prelude::*;
 use bevy_seedling::sample::AudioSample;
 
-use crate::{asset_tracking::LoadResource, audio::sound_effect};
+use crate::{asset_tracking::AssetCollection, audio::sound_effect};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_observer(apply_interaction_palette_on_click);
     app.add_observer(apply_interaction_palette_on_over);
     app.add_observer(apply_interaction_palette_on_out);
 
-    app.load_resource::<InteractionAssets>();
     app.add_observer(play_sound_effect_on_click);
     app.add_observer(play_sound_effect_on_over);
 }
@@ -57,25 +56,17 @@ fn apply_interaction_palette_on_out(
     *bg = palette.none.into();
 }
 
-#[derive(Resource, Asset, Clone, Reflect)]
+#[derive(Resource, Asset, Clone, Reflect, AssetCollection)]
 #[reflect(Resource)]
 pub struct InteractionAssets {
     #[dependency]
+    #[asset(group = "ui")]
     pub hover: Handle<AudioSample>,
     #[dependency]
+    #[asset(group = "ui")]
     pub click: Handle<AudioSample>,
 }
 
-impl FromWorld for InteractionAssets {
-    fn from_world(world: &mut World) -> Self {
-        let assets = world.resource::<AssetServer>();
-        Self {
-            hover: assets.load("audio/sound_effects/623175__aphom000__button-click-selection.wav"),
-            click: assets.load("audio/sound_effects/467951__benzix2__ui-button-click.ogg"),
-        }
-    }
-}
-
 fn play_sound_effect_on_click(
     out: On<Pointer<Click>>,
     interaction_assets: If<Res<InteractionAssets>>,
@@ -0,0 +1,29 @@
+//! A progress bar widget, see [`widget::progress_bar`](super::widget::progress_bar).
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, fill_progress_bars);
+}
+
+/// How full a progress bar widget is, from 0 to 1.
+#[derive(Component, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ProgressBar(pub f32);
+
+/// The inner node of a progress bar, sized according to its [`ProgressBar`].
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct ProgressBarFill;
+
+fn fill_progress_bars(
+    bars: Query<(&ProgressBar, &Children), Changed<ProgressBar>>,
+    mut fills: Query<&mut Node, With<ProgressBarFill>>,
+) {
+    for (progress, children) in &bars {
+        let mut iter = fills.iter_many_mut(children);
+        while let Some(mut node) = iter.fetch_next() {
+            node.width = percent(progress.0.clamp(0.0, 1.0) * 100.0);
+        }
+    }
+}
@@ -7,7 +7,11 @@ use bevy::{
     prelude::*,
 };
 
-use crate::theme::{interaction::InteractionPalette, palette::*};
+use crate::theme::{
+    interaction::InteractionPalette,
+    palette::*,
+    progress::{ProgressBar, ProgressBarFill},
+};
 
 /// A root UI node that fills the window and centers its content.
 pub fn ui_root(name: impl Into<Cow<'static, str>>) -> impl Bundle {
@@ -48,6 +52,33 @@ pub fn label(text: impl Into<String>) -> impl Bundle {
     )
 }
 
+/// A horizontal bar, filled according to its [`ProgressBar`].
+pub fn progress_bar(progress: f32) -> impl Bundle {
+    (
+        Name::new("Progress Bar"),
+        ProgressBar(progress),
+        Node {
+            width: px(380),
+            height: px(24),
+            border: UiRect::all(px(3)),
+            border_radius: BorderRadius::MAX,
+            ..default()
+        },
+        BorderColor::all(LABEL_TEXT),
+        children![(
+            Name::new("Progress Bar Fill"),
+            ProgressBarFill,
+            Node {
+                width: percent(progress.clamp(0.0, 1.0) * 100.0),
+                height: percent(100),
+                border_radius: BorderRadius::MAX,
+                ..default()
+            },
+            BackgroundColor(BUTTON_BACKGROUND),
+        )],
+    )
+}
+
 /// A large rounded button with text and an action defined as an [`Observer`].
 pub fn button<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
 where